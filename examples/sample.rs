@@ -1,8 +1,9 @@
 use asciirend::{
-    color::{ColorConvParams, TermColorMode},
+    color::{ColorConvParams, Quantization, RampPreset, TermColorMode},
     dithering::XorShufDither,
     extra::{camera_controller::CameraController, create_transform, Ctx},
-    material::{Diffuse, Material},
+    light::Light,
+    material::{Diffuse, FrameContext, Material},
     *,
 };
 use crossterm::{
@@ -182,8 +183,11 @@ fn main() -> anyhow::Result<()> {
         // We are able to set how colors will be represented on the terminal
         let color_conv = ColorConvParams {
             colors: TermColorMode::Col256,
+            ramp: RampPreset::Classic,
+            quantization: Quantization::Artistic,
+            palette: Default::default(),
         };
-        let conv_params = (color_conv, ());
+        let conv_params = (color_conv, color_conv.char_ramp());
 
         let updates = time.elapsed();
 
@@ -192,7 +196,12 @@ fn main() -> anyhow::Result<()> {
         renderer.clear_screen(&bg, &conv_params, &mut dithering, &mut buf, w, h);
 
         renderer.render(
-            &camera,
+            FrameInput {
+                camera: &camera,
+                lights: &[Light::default()],
+                time: start.as_secs_f32(),
+                frame,
+            },
             &conv_params,
             materials,
             &objects,
@@ -200,7 +209,7 @@ fn main() -> anyhow::Result<()> {
             &mut buf,
         );
 
-        renderer.text_pass(&objects, &mut buf);
+        renderer.text_pass(&objects, &conv_params.1, &mut buf);
 
         let rendered = time.elapsed();
 
@@ -269,13 +278,14 @@ struct NormalShading {
 }
 
 impl Material for NormalShading {
-    fn new_frame(&mut self) {
+    fn new_frame(&mut self, _: &FrameContext) {
         self.normals.clear();
     }
 
     fn primitive_shade(
         &mut self,
         mut pri: Primitive,
+        _: &VertexAttrs,
         proj: na::Matrix4<f32>,
         model: na::Matrix4<f32>,
     ) -> (usize, Primitive) {
@@ -302,6 +312,11 @@ impl Material for NormalShading {
                 *start = model * proj * *start;
                 *end = model * proj * *end;
 
+                Default::default()
+            }
+            Primitive::Point(Point { pos, .. }) => {
+                *pos = proj * model * *pos;
+
                 Default::default()
             }
         };
@@ -311,7 +326,13 @@ impl Material for NormalShading {
         (idx, pri)
     }
 
-    fn fragment_shade(&self, triangle: usize, _pos: Vector2, _: f32) -> Option<na::Vector4<f32>> {
+    fn fragment_shade(
+        &self,
+        triangle: usize,
+        _pos: Vector2,
+        _: f32,
+        _: Vector3,
+    ) -> Option<na::Vector4<f32>> {
         let color = (self.normals[triangle] + na::vector![1.0, 1.0, 1.0]) * 0.5;
         Some(na::vector![color.x, color.y, color.z, 1.0])
     }
@@ -51,7 +51,8 @@ class Pixel:
         self.r = vals[0]
         self.g = vals[1]
         self.b = vals[2]
-        self.c = vals[3]
+        self.style = vals[3]
+        self.c = struct.unpack('<I', vals[4:8])[0]
 
 class Pixels:
     def __init__(self, ptr, w, h):
@@ -65,13 +66,13 @@ class Pixels:
     def read(self):
         out = []
 
-        data = ar.memory.read(store, self.ptr, self.ptr + (self.w * self.h) * 4)
+        data = ar.memory.read(store, self.ptr, self.ptr + (self.w * self.h) * 8)
 
         for y in range(self.h):
             row = []
             for x in range(self.w):
-                pos = (self.w * y + x) * 4
-                d = data[pos:pos + 4]
+                pos = (self.w * y + x) * 8
+                d = data[pos:pos + 8]
                 row.append(Pixel(d))
             out.append(row)
 
@@ -80,13 +81,13 @@ class Pixels:
     def to_string(self):
         out = ""
         for row in self.read():
-            out += bytes([x.c for x in row]).decode('utf-8')
+            out += ''.join(chr(x.c) for x in row)
             out += '\n'
         return out
 
     def draw(self):
         for row in self.read():
-            print(bytes([x.c for x in row]).decode('utf-8'))
+            print(''.join(chr(x.c) for x in row))
 
 def scene_from_json(json):
     s = String(json)
@@ -1,6 +1,9 @@
 //! Color related types and functions.
 
-use crate::{Dithering, Vector3};
+use crate::{Dithering, Vector3, Vector4};
+#[cfg(feature = "serde")]
+use alloc::string::String;
+use alloc::{borrow::Cow, collections::BTreeMap, vec::Vec};
 use colorsys::{Ansi256, Hsl, Rgb};
 use nalgebra as na;
 
@@ -89,6 +92,273 @@ impl Col16 {
         assert!(idx < 16);
         unsafe { core::mem::transmute(idx as u8) }
     }
+
+    /// Returns the slot of the color in the ANSI palette.
+    pub fn ansi_index(&self) -> usize {
+        const ANSI: [u8; 16] = [0, 8, 7, 15, 1, 3, 2, 6, 4, 5, 9, 11, 10, 14, 12, 13];
+        ANSI[*self as usize] as usize
+    }
+}
+
+/// Colors of the 16 ANSI palette slots, as displayed by the terminal.
+///
+/// Terminal themes, such as Solarized or Gruvbox, freely redefine these colors, and quantizing
+/// against the idealized colors of [`Col16`] then picks the wrong slots. Colors are listed in ANSI
+/// order - black, red, green, yellow, blue, magenta, cyan, white, followed by their bright
+/// variants.
+///
+/// With serde, the palette is a list of 16 `#rrggbb` strings, which fits JSON and TOML
+/// configuration files. X resources are loaded with [`Palette16::from_xresources`].
+#[cfg_attr(
+    all(not(target_os = "wasi"), feature = "wasm-bindgen"),
+    wasm_bindgen::prelude::wasm_bindgen
+)]
+#[cfg_attr(feature = "pyo3", pyo3::pyclass)]
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(try_from = "Vec<String>", into = "Vec<String>")
+)]
+#[repr(C)]
+pub struct Palette16 {
+    colors: [[u8; 3]; 16],
+    /// The colors in OKLab space, converted once for perceptual matching.
+    lab: [Vector3; 16],
+}
+
+impl PartialEq for Palette16 {
+    fn eq(&self, other: &Self) -> bool {
+        self.colors == other.colors
+    }
+}
+
+impl Eq for Palette16 {}
+
+impl Default for Palette16 {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+/// Idealized colors, that [`Col16`] assumes.
+const STANDARD_COLORS: [[u8; 3]; 16] = [
+    [0, 0, 0],
+    [128, 0, 0],
+    [0, 128, 0],
+    [128, 128, 0],
+    [0, 0, 128],
+    [128, 0, 128],
+    [0, 128, 128],
+    [191, 191, 191],
+    [128, 128, 128],
+    [255, 0, 0],
+    [0, 255, 0],
+    [255, 255, 0],
+    [0, 0, 255],
+    [255, 0, 255],
+    [0, 255, 255],
+    [255, 255, 255],
+];
+
+impl Palette16 {
+    /// Returns the idealized colors, that [`Col16`] assumes.
+    pub fn standard() -> Self {
+        Self::new(STANDARD_COLORS)
+    }
+
+    /// Creates a palette from colors in ANSI order.
+    pub fn new(colors: [[u8; 3]; 16]) -> Self {
+        Self {
+            colors,
+            lab: colors.map(|c| srgb_to_oklab(Vector3::from(c.map(|v| v as f32 / 255.0)))),
+        }
+    }
+
+    /// Returns the colors of the palette in ANSI order.
+    pub fn colors(&self) -> &[[u8; 3]; 16] {
+        &self.colors
+    }
+
+    /// Parses a palette from exactly 16 colors in ANSI order.
+    ///
+    /// Colors are written as `#rrggbb`, with optional `#`, or as X11 `rgb:r/g/b`.
+    pub fn from_hex<'a>(colors: impl IntoIterator<Item = &'a str>) -> Option<Self> {
+        let mut out = [[0; 3]; 16];
+        let mut colors = colors.into_iter();
+
+        for o in &mut out {
+            *o = parse_color(colors.next()?)?;
+        }
+
+        colors.next().is_none().then(|| Self::new(out))
+    }
+
+    /// Parses a palette from X resources, such as the contents of `~/.Xresources`.
+    ///
+    /// All of `color0` to `color15` must be defined, under any application scope (`*color0`,
+    /// `*.color0`, `URxvt.color0`). Values may refer to `#define`d names, as is common in themes.
+    pub fn from_xresources(src: &str) -> Option<Self> {
+        let mut defines = BTreeMap::new();
+        let mut colors = [None; 16];
+
+        for line in src.lines().map(str::trim) {
+            if let Some(define) = line.strip_prefix("#define") {
+                let mut parts = define.split_whitespace();
+                if let (Some(name), Some(value)) = (parts.next(), parts.next()) {
+                    defines.insert(name, value);
+                }
+                continue;
+            }
+
+            if line.starts_with('!') {
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+
+            let slot = key
+                .trim()
+                .rsplit(['*', '.'])
+                .next()
+                .and_then(|k| k.strip_prefix("color"))
+                .and_then(|i| i.parse::<usize>().ok());
+
+            if let Some(color) = slot.and_then(|i| colors.get_mut(i)) {
+                let value = value.trim();
+                *color = parse_color(defines.get(value).copied().unwrap_or(value));
+            }
+        }
+
+        let mut out = [[0; 3]; 16];
+
+        for (o, c) in out.iter_mut().zip(colors) {
+            *o = c?;
+        }
+
+        Some(Self::new(out))
+    }
+
+    /// Returns the actual color of given palette entry.
+    pub fn color(&self, col: Col16) -> Vector3 {
+        Vector3::from(self.colors[col.ansi_index()].map(|v| v as f32 / 255.0))
+    }
+
+    /// Returns whether the palette holds the idealized colors of [`Col16`].
+    pub fn is_standard(&self) -> bool {
+        self.colors == STANDARD_COLORS
+    }
+}
+
+/// Parses `#rrggbb`, `rrggbb`, or `rgb:r/g/b` color, with 1 to 4 hex digits per channel.
+fn parse_color(s: &str) -> Option<[u8; 3]> {
+    let s = s.trim();
+
+    if let Some(rgb) = s.strip_prefix("rgb:") {
+        let mut out = [0; 3];
+        let mut channels = rgb.split('/');
+
+        for o in &mut out {
+            let c = channels.next()?;
+            if !(1..=4).contains(&c.len()) || !is_hex(c) {
+                return None;
+            }
+            let max = (1u32 << (4 * c.len())) - 1;
+            *o = (u32::from_str_radix(c, 16).ok()? * 255 / max) as u8;
+        }
+
+        return channels.next().is_none().then_some(out);
+    }
+
+    let hex = s.strip_prefix('#').unwrap_or(s);
+
+    if hex.len() != 6 || !is_hex(hex) {
+        return None;
+    }
+
+    let mut out = [0; 3];
+
+    for (i, o) in out.iter_mut().enumerate() {
+        *o = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+    }
+
+    Some(out)
+}
+
+/// Returns whether `s` consists of hex digits only.
+///
+/// Unlike `from_str_radix`, this rejects leading `+` signs.
+fn is_hex(s: &str) -> bool {
+    s.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<Vec<String>> for Palette16 {
+    type Error = &'static str;
+
+    fn try_from(colors: Vec<String>) -> Result<Self, Self::Error> {
+        Self::from_hex(colors.iter().map(String::as_str))
+            .ok_or("expected 16 colors in #rrggbb format")
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<Palette16> for Vec<String> {
+    fn from(palette: Palette16) -> Self {
+        palette
+            .colors
+            .iter()
+            .map(|[r, g, b]| alloc::format!("#{r:02x}{g:02x}{b:02x}"))
+            .collect()
+    }
+}
+
+/// Parameters of [`Col16`] quantization.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Col16Params {
+    pub quantization: Quantization,
+    /// Actual colors of the terminal.
+    ///
+    /// The artistic mapping is tuned for the standard palette, therefore, custom palettes are
+    /// always matched perceptually.
+    pub palette: Palette16,
+}
+
+/// Text attributes of a pixel.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Style {
+    pub bold: bool,
+    pub underline: bool,
+    pub blink: bool,
+}
+
+/// Output of fragment shading.
+///
+/// Besides color, materials may pick the character and text attributes of the pixel themselves.
+/// Pixel types that do not contain characters ignore the glyph, while the style is only kept by
+/// [`Styled`] pixels.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Fragment {
+    /// RGBA color of the fragment.
+    ///
+    /// Fragments with alpha below 0.5 are not drawn, but still occlude the ones behind them.
+    pub color: Vector4,
+    /// Character to draw, instead of one picked by color quantization.
+    pub glyph: Option<char>,
+    pub style: Style,
+}
+
+impl From<Vector4> for Fragment {
+    fn from(color: Vector4) -> Self {
+        Self {
+            color,
+            glyph: None,
+            style: Style::default(),
+        }
+    }
 }
 
 /// General pixel quantization trait.
@@ -113,35 +383,209 @@ pub trait QuantizePixel: Clone {
         x: usize,
         y: usize,
     ) -> Self;
+
+    /// Quantizes a shaded fragment.
+    ///
+    /// By default, this quantizes the color of the fragment. Pixel types that hold characters
+    /// should override this to honour [`Fragment::glyph`].
+    fn quantize_fragment(
+        params: &Self::Params,
+        frag: &Fragment,
+        dithering: &impl Dithering,
+        x: usize,
+        y: usize,
+    ) -> Self {
+        Self::quantize_color(params, frag.color.xyz(), dithering, x, y)
+    }
+}
+
+/// Characters, that represent brightness levels, from the darkest to the brightest.
+///
+/// Used as the parameters of character pixels. Byte (`u8`) pixels can only hold ASCII
+/// characters, and replace the rest with `?`, same as with embedded text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CharRamp {
+    chars: Cow<'static, [char]>,
+    /// How the brightness of colors is measured.
+    pub quantization: Quantization,
+}
+
+impl Default for CharRamp {
+    fn default() -> Self {
+        RampPreset::Classic.ramp()
+    }
+}
+
+impl CharRamp {
+    /// Creates a ramp from given characters, ordered from the darkest to the brightest.
+    ///
+    /// Returns `None`, if there are no characters.
+    pub fn new(chars: impl IntoIterator<Item = char>) -> Option<Self> {
+        let chars: Vec<char> = chars.into_iter().collect();
+
+        if chars.is_empty() {
+            None
+        } else {
+            Some(Self {
+                chars: chars.into(),
+                quantization: Quantization::default(),
+            })
+        }
+    }
+
+    pub fn chars(&self) -> &[char] {
+        &self.chars
+    }
+
+    /// Picks the character of given luminance.
+    fn quantize(&self, inp: Vector3, dithering: &impl Dithering, x: usize, y: usize) -> char {
+        let v = match self.quantization {
+            Quantization::Artistic => inp.dot(&na::vector![0.21, 0.72, 0.07]),
+            Quantization::Perceptual => srgb_to_oklab(inp).x,
+        };
+        self.chars[dithered_range(v, self.chars.len() - 1, dithering, x, y)]
+    }
+
+    /// Returns the character, that replaces `c` around embedded text.
+    ///
+    /// The darkest character is kept, and the others are replaced with the second darkest one.
+    fn darken(&self, c: char) -> char {
+        let darkest = self.chars[0];
+
+        if c == darkest {
+            darkest
+        } else {
+            self.chars.get(1).copied().unwrap_or(darkest)
+        }
+    }
+}
+
+/// Built-in character ramps.
+#[cfg_attr(
+    all(not(target_os = "wasi"), feature = "wasm-bindgen"),
+    wasm_bindgen::prelude::wasm_bindgen
+)]
+#[cfg_attr(feature = "pyo3", pyo3::pyclass)]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RampPreset {
+    /// ` .:-=+*%#@`
+    #[default]
+    Classic = 0,
+    /// ` .oO@`, which has clearer transitions at low resolutions.
+    Short = 1,
+    /// Paul Bourke's 70 level ramp, for fine detail at high resolutions.
+    Detailed = 2,
+    /// ` ░▒▓█` block shades.
+    Blocks = 3,
+    /// Braille patterns with increasing dot count.
+    Braille = 4,
+}
+
+impl RampPreset {
+    pub fn ramp(self) -> CharRamp {
+        const CLASSIC: [char; 10] = [' ', '.', ':', '-', '=', '+', '*', '%', '#', '@'];
+        const SHORT: [char; 5] = [' ', '.', 'o', 'O', '@'];
+        const DETAILED: [char; 70] = [
+            ' ', '.', '\'', '`', '^', '"', ',', ':', ';', 'I', 'l', '!', 'i', '>', '<', '~', '+',
+            '_', '-', '?', ']', '[', '}', '{', '1', ')', '(', '|', '\\', '/', 't', 'f', 'j', 'r',
+            'x', 'n', 'u', 'v', 'c', 'z', 'X', 'Y', 'U', 'J', 'C', 'L', 'Q', '0', 'O', 'Z', 'm',
+            'w', 'q', 'p', 'd', 'b', 'k', 'h', 'a', 'o', '*', '#', 'M', 'W', '&', '8', '%', 'B',
+            '@', '$',
+        ];
+        const BLOCKS: [char; 5] = [' ', '░', '▒', '▓', '█'];
+        const BRAILLE: [char; 9] = [' ', '⠁', '⠃', '⠇', '⠏', '⠟', '⠿', '⡿', '⣿'];
+
+        let chars: &'static [char] = match self {
+            Self::Classic => &CLASSIC,
+            Self::Short => &SHORT,
+            Self::Detailed => &DETAILED,
+            Self::Blocks => &BLOCKS,
+            Self::Braille => &BRAILLE,
+        };
+
+        CharRamp {
+            chars: Cow::Borrowed(chars),
+            quantization: Quantization::default(),
+        }
+    }
 }
 
 impl QuantizePixel for u8 {
-    type Params = ();
+    type Params = CharRamp;
 
     fn quantize_color(
-        _: &Self::Params,
+        params: &Self::Params,
         inp: Vector3,
         dithering: &impl Dithering,
         x: usize,
         y: usize,
     ) -> u8 {
-        // TODO: do HSV or even LAB based grayscale conversion
-        //let v = inp.dot(&na::vector![1.0, 1.0, 1.0]).min(1.0);
-        let v = inp.dot(&na::vector![0.21, 0.72, 0.07]);
-        to_palette(v, dithering, x, y)
+        let c = params.quantize(inp, dithering, x, y);
+        if c.is_ascii() {
+            c as u8
+        } else {
+            b'?'
+        }
+    }
+
+    fn quantize_fragment(
+        params: &Self::Params,
+        frag: &Fragment,
+        dithering: &impl Dithering,
+        x: usize,
+        y: usize,
+    ) -> u8 {
+        match frag.glyph {
+            Some(c) if c.is_ascii() => c as u8,
+            _ => Self::quantize_color(params, frag.color.xyz(), dithering, x, y),
+        }
+    }
+}
+
+impl QuantizePixel for char {
+    type Params = CharRamp;
+
+    fn quantize_color(
+        params: &Self::Params,
+        inp: Vector3,
+        dithering: &impl Dithering,
+        x: usize,
+        y: usize,
+    ) -> char {
+        params.quantize(inp, dithering, x, y)
+    }
+
+    fn quantize_fragment(
+        params: &Self::Params,
+        frag: &Fragment,
+        dithering: &impl Dithering,
+        x: usize,
+        y: usize,
+    ) -> char {
+        frag.glyph
+            .unwrap_or_else(|| Self::quantize_color(params, frag.color.xyz(), dithering, x, y))
     }
 }
 
 pub trait PixelDarken {
     /// Used around text to give more contrast around the text.
-    fn darken(&mut self);
+    ///
+    /// Characters are darkened within `ramp`, which should be the one, the pixels were quantized
+    /// with.
+    fn darken(&mut self, ramp: &CharRamp);
 }
 
 impl PixelDarken for u8 {
-    fn darken(&mut self) {
-        if *self != PALETTE[0] {
-            *self = PALETTE[1]
-        }
+    fn darken(&mut self, ramp: &CharRamp) {
+        let c = ramp.darken(*self as char);
+        *self = if c.is_ascii() { c as u8 } else { b'?' };
+    }
+}
+
+impl PixelDarken for char {
+    fn darken(&mut self, ramp: &CharRamp) {
+        *self = ramp.darken(*self);
     }
 }
 
@@ -160,7 +604,11 @@ impl PixelText for u8 {
     }
 }
 
-const PALETTE: [u8; 10] = *b" .:-=+*%#@";
+impl PixelText for char {
+    fn embed(&mut self, c: char) {
+        *self = c;
+    }
+}
 
 fn dithered_range(
     val: f32,
@@ -169,6 +617,8 @@ fn dithered_range(
     x: usize,
     y: usize,
 ) -> usize {
+    // Unlike clamp, this maps NaN to the top level.
+    #[allow(clippy::manual_clamp)]
     let val = val.min(1.0).max(0.0) * max_val as f32;
     let floored = libm::floorf(val);
     let interp = val - floored;
@@ -182,8 +632,93 @@ fn dithered_range(
     }
 }
 
-fn to_palette(val: f32, dithering: &impl Dithering, x: usize, y: usize) -> u8 {
-    PALETTE[dithered_range(val, PALETTE.len() - 1, dithering, x, y)]
+/// How colors are mapped to limited palettes.
+#[cfg_attr(
+    all(not(target_os = "wasi"), feature = "wasm-bindgen"),
+    wasm_bindgen::prelude::wasm_bindgen
+)]
+#[cfg_attr(feature = "pyo3", pyo3::pyclass)]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Quantization {
+    /// Hand tuned mapping, which favours vivid colors over accuracy.
+    ///
+    /// The hand tuning only applies to 16 colors. 256 colors are dithered between the xterm cube
+    /// levels around each channel, or the gray levels around near-neutral colors, and brightness
+    /// of characters is measured as luma.
+    #[default]
+    Artistic = 0,
+    /// Mapping to the closest colors in OKLab space, which is perceptually uniform.
+    ///
+    /// Colors are dithered between the two nearest palette entries, and brightness is measured
+    /// as OKLab lightness.
+    Perceptual = 1,
+}
+
+/// Converts an sRGB color to OKLab.
+fn srgb_to_oklab(c: Vector3) -> Vector3 {
+    let linear = c.map(|v| {
+        let v = v.clamp(0.0, 1.0);
+        if v <= 0.04045 {
+            v / 12.92
+        } else {
+            libm::powf((v + 0.055) / 1.055, 2.4)
+        }
+    });
+
+    let lms = na::matrix![
+        0.412_221_46, 0.536_332_55, 0.051_445_995;
+        0.211_903_5, 0.680_699_5, 0.107_396_96;
+        0.088_302_46, 0.281_718_85, 0.629_978_7;
+    ] * linear;
+
+    na::matrix![
+        0.210_454_26, 0.793_617_8, -0.004_072_047;
+        1.977_998_5, -2.428_592_2, 0.450_593_7;
+        0.025_904_037, 0.782_771_77, -0.808_675_77;
+    ] * lms.map(libm::cbrtf)
+}
+
+/// Picks one of the two closest palette colors in OKLab space, dithering between them.
+///
+/// The second color is the one that best brackets the input together with the closest one, that
+/// is, the input is nearest to the line segment between them. `palette` holds colors in OKLab
+/// space, and must not be empty.
+fn nearest_pair<T: Copy, const N: usize>(
+    inp: Vector3,
+    palette: [(T, Vector3); N],
+    dithering: &impl Dithering,
+    x: usize,
+    y: usize,
+) -> T {
+    let inp = srgb_to_oklab(inp);
+    let dist = |c: Vector3| (c - inp).norm_squared();
+
+    let (a, a_lab) =
+        palette.iter().fold(
+            palette[0],
+            |best, &c| if dist(c.1) < dist(best.1) { c } else { best },
+        );
+
+    // Position along the segment towards each other color, and distance to that position.
+    let (b, t, _) = palette
+        .iter()
+        .filter_map(|&(b, b_lab)| {
+            let axis = b_lab - a_lab;
+            let t = (inp - a_lab).dot(&axis) / axis.norm_squared();
+            (t > 0.0 && t.is_finite()).then(|| {
+                let t = t.min(1.0);
+                (b, t, dist(a_lab + axis * t))
+            })
+        })
+        .min_by(|(_, _, a), (_, _, b)| a.total_cmp(b))
+        .unwrap_or((a, 0.0, 0.0));
+
+    if libm::roundf(dithering.dither(t, x, y, 0)) >= 1.0 {
+        b
+    } else {
+        a
+    }
 }
 
 /// Converts to hsv (not hsl!)
@@ -207,15 +742,23 @@ fn to_hsv(rgb: Rgb) -> Hsl {
 }
 
 impl QuantizePixel for Col16 {
-    type Params = ();
+    type Params = Col16Params;
 
     fn quantize_color(
-        _: &Self::Params,
+        params: &Self::Params,
         inp: Vector3,
         dithering: &impl Dithering,
         x: usize,
         y: usize,
     ) -> Col16 {
+        if params.quantization == Quantization::Perceptual || !params.palette.is_standard() {
+            let palette: [_; 16] = core::array::from_fn(|i| {
+                let col = Col16::from_idx(i);
+                (col, params.palette.lab[col.ansi_index()])
+            });
+            return nearest_pair(inp, palette, dithering, x, y);
+        }
+
         fn nearest_colors(
             inp: Hsl,
             dither_value: f32,
@@ -225,8 +768,8 @@ impl QuantizePixel for Col16 {
             // Because we can only blend between 2 colors, we apply dithering to transition between
             // grayscale and color blending.
             let (a, b) = if inp.saturation() / 100.0 < dither_value as f64 * 0.33 {
-                // Only grayscale here, based on value. Simple.
-                let val = inp.lightness() as f32 / 400.0;
+                // Only grayscale here, based on value. Simple. Value spans the 4 grays.
+                let val = inp.lightness() as f32 / 100.0 * 3.0;
                 let floor = libm::floorf(val);
                 let ceil = libm::ceilf(val);
                 let idx1 = floor as usize;
@@ -259,7 +802,7 @@ impl QuantizePixel for Col16 {
                 // of 4 possible ones.
                 let phase = (phase + PI - PI * (dither_value2 - 0.5)) % (2.0 * PI) - PI;
 
-                if phase <= 3.0 * PI / 4.0 && phase >= PI / 4.0 {
+                if (PI / 4.0..=3.0 * PI / 4.0).contains(&phase) {
                     // Ceiled hue, diff lightness
                     let idx = Col16::DarkRed as usize + (libm::ceil(q_hue) as usize % 6);
                     (
@@ -272,7 +815,7 @@ impl QuantizePixel for Col16 {
                             ((-value_rat).max(-1.0) + 1.0) as f32 / 2.0,
                         ),
                     )
-                } else if phase <= PI / 4.0 && phase >= -PI / 4.0 {
+                } else if (-PI / 4.0..=PI / 4.0).contains(&phase) {
                     // Ceiled lightness, diff hues
                     let idx1 = Col16::DarkRed as usize + libm::floor(q_hue) as usize;
                     let idx2 = Col16::DarkRed as usize + (libm::ceil(q_hue) as usize % 6);
@@ -284,7 +827,7 @@ impl QuantizePixel for Col16 {
                         (a, (hue_rat + 1.0) as f32 * 30.0),
                         (b, (-hue_rat + 1.0) as f32 * 30.0),
                     )
-                } else if phase <= -PI / 4.0 && phase >= -3.0 * PI / 4.0 {
+                } else if (-3.0 * PI / 4.0..=-PI / 4.0).contains(&phase) {
                     // Floored hue, diff lightness
                     let idx = Col16::DarkRed as usize + libm::floor(q_hue) as usize;
                     (
@@ -334,7 +877,7 @@ impl QuantizePixel for Col16 {
 }
 
 impl PixelDarken for Col16 {
-    fn darken(&mut self) {
+    fn darken(&mut self, _: &CharRamp) {
         match self {
             Self::White => *self = Self::Gray,
             Self::Gray => *self = Self::DarkGray,
@@ -371,12 +914,123 @@ impl<A: QuantizePixel, B: QuantizePixel> QuantizePixel for (A, B) {
             B::quantize_color(p_b, inp, dithering, x, y),
         )
     }
+
+    fn quantize_fragment(
+        (p_a, p_b): &(A::Params, B::Params),
+        frag: &Fragment,
+        dithering: &impl Dithering,
+        x: usize,
+        y: usize,
+    ) -> (A, B) {
+        (
+            A::quantize_fragment(p_a, frag, dithering, x, y),
+            B::quantize_fragment(p_b, frag, dithering, x, y),
+        )
+    }
+}
+
+/// Pixel, that may be empty.
+///
+/// Pixels are only ever set to `Some`, therefore, buffers filled with `None` tell which cells were
+/// drawn to, when rendering over a [`Backdrop::Transparent`](crate::Backdrop::Transparent)
+/// background. Empty pixels ignore darkening, while embedding text into them creates a default
+/// pixel, so that text (such as [`ColorBar`](crate::material::ColorBar) labels) stays visible over
+/// empty cells.
+impl<T: QuantizePixel> QuantizePixel for Option<T> {
+    type Params = T::Params;
+
+    fn quantize_color(
+        params: &Self::Params,
+        inp: Vector3,
+        dithering: &impl Dithering,
+        x: usize,
+        y: usize,
+    ) -> Self {
+        Some(T::quantize_color(params, inp, dithering, x, y))
+    }
+
+    fn quantize_fragment(
+        params: &Self::Params,
+        frag: &Fragment,
+        dithering: &impl Dithering,
+        x: usize,
+        y: usize,
+    ) -> Self {
+        Some(T::quantize_fragment(params, frag, dithering, x, y))
+    }
+}
+
+impl<T: PixelDarken> PixelDarken for Option<T> {
+    fn darken(&mut self, ramp: &CharRamp) {
+        if let Some(pixel) = self {
+            pixel.darken(ramp);
+        }
+    }
+}
+
+impl<T: PixelText + Default> PixelText for Option<T> {
+    fn embed(&mut self, c: char) {
+        self.get_or_insert_with(T::default).embed(c);
+    }
+}
+
+/// Pixel with text attributes.
+///
+/// Wraps any other pixel type, and records the [`Style`] of the fragment it was quantized from.
+/// Background and embedded text pixels are unstyled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Styled<T> {
+    pub pixel: T,
+    pub style: Style,
+}
+
+impl<T: QuantizePixel> QuantizePixel for Styled<T> {
+    type Params = T::Params;
+
+    fn quantize_color(
+        params: &Self::Params,
+        inp: Vector3,
+        dithering: &impl Dithering,
+        x: usize,
+        y: usize,
+    ) -> Self {
+        Self {
+            pixel: T::quantize_color(params, inp, dithering, x, y),
+            style: Style::default(),
+        }
+    }
+
+    fn quantize_fragment(
+        params: &Self::Params,
+        frag: &Fragment,
+        dithering: &impl Dithering,
+        x: usize,
+        y: usize,
+    ) -> Self {
+        Self {
+            pixel: T::quantize_fragment(params, frag, dithering, x, y),
+            style: frag.style,
+        }
+    }
+}
+
+impl<T: PixelDarken> PixelDarken for Styled<T> {
+    fn darken(&mut self, ramp: &CharRamp) {
+        self.pixel.darken(ramp);
+    }
+}
+
+impl<T: PixelText> PixelText for Styled<T> {
+    fn embed(&mut self, c: char) {
+        self.pixel.embed(c);
+        self.style = Style::default();
+    }
 }
 
 impl<A: PixelDarken, B: PixelDarken> PixelDarken for (A, B) {
-    fn darken(&mut self) {
-        self.0.darken();
-        self.1.darken();
+    fn darken(&mut self, ramp: &CharRamp) {
+        self.0.darken(ramp);
+        self.1.darken(ramp);
     }
 }
 
@@ -395,6 +1049,21 @@ impl<A: PixelDarken, B: PixelText> PixelText for (A, B) {
 #[repr(C)]
 pub struct ColorConvParams {
     pub colors: TermColorMode,
+    /// Characters of pixels, that hold both color and character.
+    pub ramp: RampPreset,
+    pub quantization: Quantization,
+    /// Actual colors of the terminal, used in 16 color mode.
+    pub palette: Palette16,
+}
+
+impl ColorConvParams {
+    /// Returns the character ramp of these parameters.
+    pub fn char_ramp(&self) -> CharRamp {
+        CharRamp {
+            quantization: self.quantization,
+            ..self.ramp.ramp()
+        }
+    }
 }
 
 #[cfg_attr(
@@ -411,6 +1080,92 @@ pub enum TermColorMode {
     Rgb = 3,
 }
 
+/// Channel levels of the 6x6x6 color cube of the xterm 256 color palette.
+const XTERM_CUBE: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// Gray levels of the xterm 256 color palette, both from the color cube and the grayscale ramp.
+const XTERM_GRAYS: [u8; 30] = [
+    0, 8, 18, 28, 38, 48, 58, 68, 78, 88, 95, 98, 108, 118, 128, 135, 138, 148, 158, 168, 175, 178,
+    188, 198, 208, 215, 218, 228, 238, 255,
+];
+
+/// Palette codes of [`XTERM_GRAYS`].
+const XTERM_GRAY_CODES: [u8; 30] = [
+    16, 232, 233, 234, 235, 236, 237, 238, 239, 240, 59, 241, 242, 243, 244, 102, 245, 246, 247,
+    248, 145, 249, 250, 251, 252, 188, 253, 254, 255, 231,
+];
+
+/// Returns indices of the ascending `levels` surrounding given value.
+fn bracket(levels: &[u8], v: f32) -> [usize; 2] {
+    let v = v.clamp(0.0, 1.0) * 255.0;
+    let hi = levels
+        .iter()
+        .position(|&l| l as f32 >= v)
+        .unwrap_or(levels.len() - 1)
+        .max(1);
+    [hi - 1, hi]
+}
+
+/// Picks one of the `levels` surrounding given value, dithering between them.
+fn dithered_level(
+    levels: &[u8],
+    v: f32,
+    dithering: &impl Dithering,
+    x: usize,
+    y: usize,
+    z: usize,
+) -> usize {
+    let [lo, hi] = bracket(levels, v);
+    let (lo_v, hi_v) = (levels[lo] as f32, levels[hi] as f32);
+    let t = (v.clamp(0.0, 1.0) * 255.0 - lo_v) / (hi_v - lo_v);
+
+    if libm::roundf(dithering.dither(t, x, y, z)) >= 1.0 {
+        hi
+    } else {
+        lo
+    }
+}
+
+fn cube_bracket(v: f32) -> [usize; 2] {
+    bracket(&XTERM_CUBE, v)
+}
+
+fn cube_code([r, g, b]: [usize; 3]) -> u8 {
+    (16 + 36 * r + 6 * g + b) as u8
+}
+
+fn cube_color(idx: [usize; 3]) -> Vector3 {
+    Vector3::from(idx.map(|i| XTERM_CUBE[i] as f32 / 255.0))
+}
+
+/// Returns indices of the grayscale ramp entries (`232..=255`) surrounding given value.
+fn gray_bracket(v: f32) -> [usize; 2] {
+    let pos = ((v * 255.0 - 8.0) / 10.0).clamp(0.0, 23.0);
+    let lo = core::cmp::min(pos as usize, 22);
+    [lo, lo + 1]
+}
+
+fn gray_color(idx: usize) -> Vector3 {
+    Vector3::repeat((8 + 10 * idx) as f32 / 255.0)
+}
+
+/// Returns the xterm 256 palette entries, among which the nearest colors to the input are.
+///
+/// These are the cube corners, and the grays around the input.
+fn xterm_neighbors(inp: Vector3) -> [(u8, Vector3); 10] {
+    let [r, g, b] = [inp.x, inp.y, inp.z].map(cube_bracket);
+    let gray = gray_bracket((inp.x + inp.y + inp.z) / 3.0);
+
+    core::array::from_fn(|i| {
+        if i < 8 {
+            let idx = [r[i >> 2], g[(i >> 1) & 1], b[i & 1]];
+            (cube_code(idx), cube_color(idx))
+        } else {
+            (232 + gray[i - 8] as u8, gray_color(gray[i - 8]))
+        }
+    })
+}
+
 #[derive(Clone)]
 pub enum TermColor {
     SingleCol,
@@ -463,20 +1218,38 @@ impl QuantizePixel for TermColor {
         match params.colors {
             SingleCol => TermColor::SingleCol,
             Col16 => TermColor::Col16(crate::color::Col16::quantize_color(
-                &(),
+                &Col16Params {
+                    quantization: params.quantization,
+                    palette: params.palette,
+                },
                 inp,
                 dithering,
                 x,
                 y,
             )),
+            Col256 if params.quantization == Quantization::Perceptual => {
+                let neighbors = xterm_neighbors(inp).map(|(code, c)| (code, srgb_to_oklab(c)));
+                let code = nearest_pair(inp, neighbors, dithering, x, y);
+                TermColor::Col256(colorsys::Ansi256::new(code))
+            }
             Col256 => {
-                // We are doing something very ugly and inaccurate here, but hey, it's fast!
-                let r = core::cmp::min(dithered_range(inp.x, 8, dithering, x, y) * 32, 255) as u8;
-                let g = core::cmp::min(dithered_range(inp.y, 8, dithering, x, y) * 32, 255) as u8;
-                let b = core::cmp::min(dithered_range(inp.z, 4, dithering, x, y) * 64, 255) as u8;
-                let rgb = colorsys::Rgb::from([r, g, b]);
-                let ansi = colorsys::Ansi256::from(rgb);
-                TermColor::Col256(ansi)
+                let max = inp.max();
+                let min = inp.min();
+
+                // Near-neutral colors have far more gray levels to pick from, than the cube offers.
+                let code = if max - min < 10.0 / 255.0 {
+                    let v = (inp.x + inp.y + inp.z) / 3.0;
+                    XTERM_GRAY_CODES[dithered_level(&XTERM_GRAYS, v, dithering, x, y, 0)]
+                } else {
+                    let [r, g, b] = [inp.x, inp.y, inp.z];
+                    cube_code([
+                        dithered_level(&XTERM_CUBE, r, dithering, x, y, 0),
+                        dithered_level(&XTERM_CUBE, g, dithering, x, y, 1),
+                        dithered_level(&XTERM_CUBE, b, dithering, x, y, 2),
+                    ])
+                };
+
+                TermColor::Col256(colorsys::Ansi256::new(code))
             }
             Rgb => TermColor::Rgb(colorsys::Rgb::new(
                 dithered_range(inp.x, 255, dithering, x, y) as f64,
@@ -489,9 +1262,9 @@ impl QuantizePixel for TermColor {
 }
 
 impl PixelDarken for TermColor {
-    fn darken(&mut self) {
+    fn darken(&mut self, ramp: &CharRamp) {
         match self {
-            Self::Col16(v) => v.darken(),
+            Self::Col16(v) => v.darken(ramp),
             Self::Col256(ansi) => {
                 let mut rgb = colorsys::Rgb::from(*ansi);
                 rgb.set_red(rgb.red() / 2.0);
@@ -509,9 +1282,174 @@ impl PixelDarken for TermColor {
     }
 }
 
+/// Terminal cell, that mixes its background and foreground colors through the glyph.
+///
+/// Glyphs cover part of the cell with the foreground color, and the rest shows the background.
+/// Picking all three together approximates colors, that are missing from the palette - for
+/// instance, cyan `:` on dark blue reads as teal. This greatly increases the effective color
+/// depth of 16 color terminals.
+///
+/// Glyphs are taken from the character ramp of the parameters, and their coverage is assumed to
+/// grow linearly along the ramp. Colors are only mixed in 16 and 256 color modes, other modes
+/// leave the background unset ([`TermColor::SingleCol`]), same as fragments with their own glyph.
+#[derive(Clone)]
+pub struct TwoTone {
+    pub foreground: TermColor,
+    pub background: TermColor,
+    pub glyph: char,
+}
+
+/// Picks the background and foreground colors, and the foreground coverage level, that mix into
+/// the color closest to the input, in OKLab space. `palette` holds colors in OKLab space.
+fn two_tone<const N: usize>(
+    inp: Vector3,
+    palette: [Vector3; N],
+    levels: usize,
+    dithering: &impl Dithering,
+    x: usize,
+    y: usize,
+) -> (usize, usize, usize) {
+    let inp = srgb_to_oklab(inp);
+    let dist = |c: Vector3| (c - inp).norm_squared();
+
+    // Solid colors win ties, because they do not add glyph noise.
+    let solid = (0..N)
+        .min_by(|&a, &b| dist(palette[a]).total_cmp(&dist(palette[b])))
+        .unwrap_or(0);
+    let mut best = (solid, solid, 0.0, dist(palette[solid]));
+
+    for (bg, bg_lab) in palette.iter().enumerate() {
+        for (fg, fg_lab) in palette.iter().enumerate() {
+            // Glyphs get denser as colors get brighter, same as in single color cells.
+            if fg_lab.x <= bg_lab.x {
+                continue;
+            }
+
+            let axis = fg_lab - bg_lab;
+            let t = ((inp - bg_lab).dot(&axis) / axis.norm_squared()).clamp(0.0, 1.0);
+            let dist = dist(bg_lab + axis * t);
+
+            if dist < best.3 {
+                best = (bg, fg, t, dist);
+            }
+        }
+    }
+
+    let (bg, fg, t, _) = best;
+
+    match dithered_range(t, levels, dithering, x, y) {
+        // Full coverage is the foreground color alone, which is better drawn as a solid cell.
+        level if level == levels && levels > 0 => (fg, fg, 0),
+        level => (bg, fg, level),
+    }
+}
+
+impl QuantizePixel for TwoTone {
+    type Params = ColorConvParams;
+
+    fn quantize_color(
+        params: &Self::Params,
+        inp: Vector3,
+        dithering: &impl Dithering,
+        x: usize,
+        y: usize,
+    ) -> Self {
+        let ramp = params.char_ramp();
+        let chars = ramp.chars();
+        let levels = chars.len() - 1;
+
+        match params.colors {
+            TermColorMode::Col16 => {
+                let palette: [_; 16] =
+                    core::array::from_fn(|i| params.palette.lab[Col16::from_idx(i).ansi_index()]);
+                let (bg, fg, level) = two_tone(inp, palette, levels, dithering, x, y);
+
+                Self {
+                    foreground: TermColor::Col16(Col16::from_idx(fg)),
+                    background: TermColor::Col16(Col16::from_idx(bg)),
+                    glyph: chars[level],
+                }
+            }
+            TermColorMode::Col256 => {
+                let neighbors = xterm_neighbors(inp);
+                let (bg, fg, level) = two_tone(
+                    inp,
+                    neighbors.map(|(_, c)| srgb_to_oklab(c)),
+                    levels,
+                    dithering,
+                    x,
+                    y,
+                );
+
+                Self {
+                    foreground: TermColor::Col256(Ansi256::new(neighbors[fg].0)),
+                    background: TermColor::Col256(Ansi256::new(neighbors[bg].0)),
+                    glyph: chars[level],
+                }
+            }
+            _ => Self {
+                foreground: TermColor::quantize_color(params, inp, dithering, x, y),
+                background: TermColor::SingleCol,
+                glyph: ramp.quantize(inp, dithering, x, y),
+            },
+        }
+    }
+
+    fn quantize_fragment(
+        params: &Self::Params,
+        frag: &Fragment,
+        dithering: &impl Dithering,
+        x: usize,
+        y: usize,
+    ) -> Self {
+        match frag.glyph {
+            Some(glyph) => Self {
+                foreground: TermColor::quantize_color(params, frag.color.xyz(), dithering, x, y),
+                background: TermColor::SingleCol,
+                glyph,
+            },
+            None => Self::quantize_color(params, frag.color.xyz(), dithering, x, y),
+        }
+    }
+}
+
+impl PixelDarken for TwoTone {
+    fn darken(&mut self, ramp: &CharRamp) {
+        self.foreground.darken(ramp);
+        self.background.darken(ramp);
+        self.glyph.darken(ramp);
+    }
+}
+
+impl PixelText for TwoTone {
+    /// Embeds the character over unset background, so that it stays readable.
+    fn embed(&mut self, c: char) {
+        self.background = TermColor::SingleCol;
+        self.glyph.embed(c);
+    }
+}
+
 #[cfg(feature = "crossterm")]
 const _: () = {
-    use crossterm::style::{Color, Colors};
+    use crossterm::style::{Attribute, Attributes, Color, Colors};
+
+    impl From<Style> for Attributes {
+        fn from(style: Style) -> Self {
+            let mut attrs = Attributes::default();
+
+            if style.bold {
+                attrs.set(Attribute::Bold);
+            }
+            if style.underline {
+                attrs.set(Attribute::Underlined);
+            }
+            if style.blink {
+                attrs.set(Attribute::SlowBlink);
+            }
+
+            attrs
+        }
+    }
 
     impl From<Col16> for Color {
         fn from(v: Col16) -> Self {
@@ -579,8 +1517,17 @@ const _: () = {
         }
     }
 
+    impl From<TwoTone> for Colors {
+        fn from(cell: TwoTone) -> Self {
+            Self {
+                foreground: cell.foreground.into(),
+                background: cell.background.into(),
+            }
+        }
+    }
+
     impl PixelDarken for Color {
-        fn darken(&mut self) {
+        fn darken(&mut self, _: &CharRamp) {
             match self {
                 Self::White => *self = Self::Grey,
                 Self::Grey => *self = Self::DarkGrey,
@@ -617,9 +1564,93 @@ const _: () = {
     }
 
     impl PixelDarken for Colors {
-        fn darken(&mut self) {
-            self.foreground.as_mut().map(|v| v.darken());
-            self.background.as_mut().map(|v| v.darken());
+        fn darken(&mut self, ramp: &CharRamp) {
+            self.foreground.as_mut().map(|v| v.darken(ramp));
+            self.background.as_mut().map(|v| v.darken(ramp));
         }
     }
 };
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{format, string::String};
+
+    #[test]
+    fn hex_colors() {
+        assert_eq!(parse_color("#0080ff"), Some([0, 128, 255]));
+        assert_eq!(parse_color(" C0c0C0 "), Some([192, 192, 192]));
+
+        for s in ["#0080f", "#0080ff0", "0080fg", "#+fffff", "#ééé", "", "#"] {
+            assert_eq!(parse_color(s), None, "{s:?}");
+        }
+    }
+
+    #[test]
+    fn rgb_colors() {
+        // Channels are scaled by the maximum value of their digit count.
+        assert_eq!(parse_color("rgb:f/8/0"), Some([255, 136, 0]));
+        assert_eq!(parse_color("rgb:ff/80/00"), Some([255, 128, 0]));
+        assert_eq!(parse_color("rgb:fff/800/0"), Some([255, 127, 0]));
+        assert_eq!(parse_color("rgb:ffff/8000/1"), Some([255, 127, 17]));
+
+        for s in [
+            "rgb:fffff/0/0",
+            "rgb:f/f",
+            "rgb:f/f/f/f",
+            "rgb://",
+            "rgb:f//f",
+            "rgb:+f/0/0",
+            "rgb:g/0/0",
+        ] {
+            assert_eq!(parse_color(s), None, "{s:?}");
+        }
+    }
+
+    #[test]
+    fn palette_from_hex() {
+        let hex: Vec<String> = STANDARD_COLORS
+            .iter()
+            .map(|[r, g, b]| format!("#{r:02x}{g:02x}{b:02x}"))
+            .collect();
+
+        let palette = Palette16::from_hex(hex.iter().map(String::as_str)).unwrap();
+        assert!(palette.is_standard());
+        assert_eq!(palette, Palette16::standard());
+        assert_eq!(palette.lab, Palette16::standard().lab);
+
+        assert!(Palette16::from_hex(hex[1..].iter().map(String::as_str)).is_none());
+        assert!(Palette16::from_hex(hex.iter().chain(&hex[..1]).map(String::as_str)).is_none());
+    }
+
+    #[test]
+    fn palette_from_xresources() {
+        let mut src = String::from(
+            "! comment\n#define fg #c0c0c0\n#define red rgb:cc/00/00\n\
+             *color0: #101010\n*.color1: red\nURxvt.color2:00ff00\n*color16: #ffffff\n\
+             *background: #000000\n*color15: fg\n",
+        );
+        for i in 3..15 {
+            src += &format!("*color{i}: #{i:02x}{i:02x}{i:02x}\n");
+        }
+
+        let palette = Palette16::from_xresources(&src).unwrap();
+        let colors = palette.colors();
+        assert_eq!(colors[0], [16, 16, 16]);
+        assert_eq!(colors[1], [204, 0, 0]);
+        assert_eq!(colors[2], [0, 255, 0]);
+        assert_eq!(colors[3], [3, 3, 3]);
+        assert_eq!(colors[15], [192, 192, 192]);
+        assert!(!palette.is_standard());
+
+        // Later definitions take precedence.
+        let palette = Palette16::from_xresources(&(src.clone() + "*color3: #ffffff\n")).unwrap();
+        assert_eq!(palette.colors()[3], [255, 255, 255]);
+
+        let missing = src.replace("*color7:", "*colour7:");
+        assert!(Palette16::from_xresources(&missing).is_none());
+
+        let invalid = src.replace("*color15: fg", "*color15: bg");
+        assert!(Palette16::from_xresources(&invalid).is_none());
+    }
+}
@@ -1,5 +1,8 @@
 use crate::{
-    color::{ColorConvParams, PixelDarken, PixelText, QuantizePixel, TermColor, TermColorMode},
+    color::{
+        ColorConvParams, Fragment, Palette16, PixelDarken, PixelText, Quantization, QuantizePixel,
+        RampPreset, Style, TermColor, TermColorMode,
+    },
     dithering::Dithering,
     extra::{
         create_transform,
@@ -85,6 +88,9 @@ pub fn scene_from_json(scene: &str) -> usize {
     let Some(scene): Option<Scene> = Some(serde_json::from_str(scene).unwrap()) else {
         return !0usize;
     };
+    if !scene.validate_objects() {
+        return !0usize;
+    }
     gs::new_scene(move || scene)
 }
 
@@ -117,7 +123,54 @@ pub extern "C" fn vec3(x: f32, y: f32, z: f32) -> Vec3 {
 #[cfg_attr(feature = "pyo3", pyfunction)]
 #[no_mangle]
 pub extern "C" fn color_conv(colors: TermColorMode) -> ColorConvParams {
-    ColorConvParams { colors }
+    color_conv_with_ramp(colors, RampPreset::Classic)
+}
+
+/// Creates color conversion parameters with given character ramp.
+///
+/// Pixels hold single bytes, thus non-ASCII ramps, such as [`RampPreset::Blocks`], come out as
+/// `?` characters.
+#[cfg_attr(all(not(target_os = "wasi"), feature = "wasm-bindgen"), wasm_bindgen)]
+#[cfg_attr(feature = "pyo3", pyfunction)]
+#[no_mangle]
+pub extern "C" fn color_conv_with_ramp(colors: TermColorMode, ramp: RampPreset) -> ColorConvParams {
+    ColorConvParams {
+        colors,
+        ramp,
+        quantization: Quantization::Artistic,
+        palette: Default::default(),
+    }
+}
+
+/// Creates color conversion parameters, that map colors perceptually, instead of artistically.
+#[cfg_attr(all(not(target_os = "wasi"), feature = "wasm-bindgen"), wasm_bindgen)]
+#[cfg_attr(feature = "pyo3", pyfunction)]
+#[no_mangle]
+pub extern "C" fn color_conv_perceptual(
+    colors: TermColorMode,
+    ramp: RampPreset,
+) -> ColorConvParams {
+    ColorConvParams {
+        colors,
+        ramp,
+        quantization: Quantization::Perceptual,
+        palette: Default::default(),
+    }
+}
+
+/// Sets the actual colors of the terminal, used in 16 color mode.
+///
+/// `palette` is either a JSON list of 16 `#rrggbb` colors in ANSI order, or X resources defining
+/// `color0` to `color15`. Returns `None`, if the palette could not be parsed.
+#[cfg_attr(all(not(target_os = "wasi"), feature = "wasm-bindgen"), wasm_bindgen)]
+#[cfg_attr(feature = "pyo3", pyfunction)]
+#[no_mangle]
+pub fn color_conv_with_palette(params: ColorConvParams, palette: &str) -> Option<ColorConvParams> {
+    let palette = serde_json::from_str(palette)
+        .ok()
+        .or_else(|| Palette16::from_xresources(palette))?;
+
+    Some(ColorConvParams { palette, ..params })
 }
 
 #[cfg_attr(all(not(target_os = "wasi"), feature = "wasm-bindgen"), wasm_bindgen)]
@@ -206,6 +259,9 @@ pub extern "C" fn set_line_points(scene: usize, obj: usize, start: Vec3, end: Ve
 }
 
 /// Renders a scene into RgbPixel slice.
+///
+/// With transparent background, cells not covered by objects or text are zeroed, including their
+/// character.
 #[cfg_attr(all(not(target_os = "wasi"), feature = "wasm-bindgen"), wasm_bindgen)]
 #[cfg_attr(feature = "pyo3", pyfunction)]
 #[no_mangle]
@@ -216,9 +272,17 @@ pub fn render(
     h: usize,
     elapsed: f64,
 ) -> Vec<RgbPixel> {
-    let mut out: Vec<RgbPixel> = vec![];
-    gs::render(scene, color_conv, &mut out, w, h, elapsed);
-    out
+    let mut out: Vec<Option<RgbPixel>> = vec![None; w * h];
+    gs::render(
+        scene,
+        color_conv,
+        &color_conv.char_ramp(),
+        &mut out,
+        w,
+        h,
+        elapsed,
+    );
+    out.into_iter().map(Option::unwrap_or_default).collect()
 }
 
 #[no_mangle]
@@ -229,7 +293,7 @@ pub extern "C" fn render_raw(
     h: usize,
     elapsed: f64,
 ) -> *mut RgbPixel {
-    let mut out = render(scene, &ColorConvParams { colors }, w, h, elapsed);
+    let mut out = render(scene, &color_conv(colors), w, h, elapsed);
     assert_eq!(out.len(), w * h);
     let ptr = out.as_mut_ptr();
     core::mem::forget(out);
@@ -318,6 +382,382 @@ pub extern "C" fn set_bg_color(scene: usize, col: Vec3) {
     });
 }
 
+/// Fills the background with the flat background color.
+#[cfg_attr(all(not(target_os = "wasi"), feature = "wasm-bindgen"), wasm_bindgen)]
+#[cfg_attr(feature = "pyo3", pyfunction)]
+#[no_mangle]
+pub extern "C" fn set_bg_solid(scene: usize) {
+    gs::with_scene(scene, |scene| {
+        scene.bg.backdrop = Backdrop::Solid;
+    });
+}
+
+/// Fills the background with a gradient from the top to the bottom of the screen.
+#[cfg_attr(all(not(target_os = "wasi"), feature = "wasm-bindgen"), wasm_bindgen)]
+#[cfg_attr(feature = "pyo3", pyfunction)]
+#[no_mangle]
+pub extern "C" fn set_bg_gradient(scene: usize, top: Vec3, bottom: Vec3) {
+    gs::with_scene(scene, |scene| {
+        scene.bg.backdrop = Backdrop::VerticalGradient {
+            top: top.into(),
+            bottom: bottom.into(),
+        };
+    });
+}
+
+/// Fills the background with a sky, that moves together with the camera.
+#[cfg_attr(all(not(target_os = "wasi"), feature = "wasm-bindgen"), wasm_bindgen)]
+#[cfg_attr(feature = "pyo3", pyfunction)]
+#[no_mangle]
+pub extern "C" fn set_bg_sky(scene: usize, zenith: Vec3, horizon: Vec3, ground: Vec3) {
+    gs::with_scene(scene, |scene| {
+        scene.bg.backdrop = Backdrop::Sky {
+            zenith: zenith.into(),
+            horizon: horizon.into(),
+            ground: ground.into(),
+        };
+    });
+}
+
+/// Fills the background with stars over the background color.
+#[cfg_attr(all(not(target_os = "wasi"), feature = "wasm-bindgen"), wasm_bindgen)]
+#[cfg_attr(feature = "pyo3", pyfunction)]
+#[no_mangle]
+pub extern "C" fn set_bg_starfield(scene: usize, density: f32, color: Vec3) {
+    gs::with_scene(scene, |scene| {
+        scene.bg.backdrop = Backdrop::Starfield {
+            density,
+            color: color.into(),
+        };
+    });
+}
+
+/// Leaves cells, that are not covered by objects, empty.
+#[cfg_attr(all(not(target_os = "wasi"), feature = "wasm-bindgen"), wasm_bindgen)]
+#[cfg_attr(feature = "pyo3", pyfunction)]
+#[no_mangle]
+pub extern "C" fn set_bg_transparent(scene: usize) {
+    gs::with_scene(scene, |scene| {
+        scene.bg.backdrop = Backdrop::Transparent;
+    });
+}
+
+/// Enables fog, that linearly thickens from `start` to `end` view depth.
+#[cfg_attr(all(not(target_os = "wasi"), feature = "wasm-bindgen"), wasm_bindgen)]
+#[cfg_attr(feature = "pyo3", pyfunction)]
+#[no_mangle]
+pub extern "C" fn set_linear_fog(scene: usize, start: f32, end: f32) {
+    gs::with_scene(scene, |scene| {
+        scene.bg.fog = Some(Fog::Linear { start, end });
+    });
+}
+
+/// Enables exponential fog, optionally with squared falloff.
+#[cfg_attr(all(not(target_os = "wasi"), feature = "wasm-bindgen"), wasm_bindgen)]
+#[cfg_attr(feature = "pyo3", pyfunction)]
+#[no_mangle]
+pub extern "C" fn set_exp_fog(scene: usize, density: f32, squared: bool) {
+    gs::with_scene(scene, |scene| {
+        scene.bg.fog = Some(if squared {
+            Fog::ExponentialSquared { density }
+        } else {
+            Fog::Exponential { density }
+        });
+    });
+}
+
+#[cfg_attr(all(not(target_os = "wasi"), feature = "wasm-bindgen"), wasm_bindgen)]
+#[cfg_attr(feature = "pyo3", pyfunction)]
+#[no_mangle]
+pub extern "C" fn clear_fog(scene: usize) {
+    gs::with_scene(scene, |scene| {
+        scene.bg.fog = None;
+    });
+}
+
+#[cfg_attr(all(not(target_os = "wasi"), feature = "wasm-bindgen"), wasm_bindgen)]
+#[cfg_attr(feature = "pyo3", pyfunction)]
+#[no_mangle]
+pub fn add_directional_light(scene: usize, direction: Vec3, color: Vec3) -> Option<usize> {
+    add_light(
+        scene,
+        Light::Directional {
+            direction: direction.into(),
+            color: color.into(),
+        },
+    )
+}
+
+#[cfg_attr(all(not(target_os = "wasi"), feature = "wasm-bindgen"), wasm_bindgen)]
+#[cfg_attr(feature = "pyo3", pyfunction)]
+#[no_mangle]
+pub fn add_point_light(scene: usize, position: Vec3, color: Vec3) -> Option<usize> {
+    add_light(
+        scene,
+        Light::Point {
+            position: position.into(),
+            color: color.into(),
+            attenuation: Default::default(),
+        },
+    )
+}
+
+#[cfg_attr(all(not(target_os = "wasi"), feature = "wasm-bindgen"), wasm_bindgen)]
+#[cfg_attr(feature = "pyo3", pyfunction)]
+#[no_mangle]
+pub fn add_spot_light(
+    scene: usize,
+    position: Vec3,
+    direction: Vec3,
+    color: Vec3,
+    inner_angle: f32,
+    outer_angle: f32,
+) -> Option<usize> {
+    add_light(
+        scene,
+        Light::Spot {
+            position: position.into(),
+            direction: direction.into(),
+            color: color.into(),
+            attenuation: Default::default(),
+            inner_angle,
+            outer_angle,
+        },
+    )
+}
+
+fn add_light(scene: usize, light: Light) -> Option<usize> {
+    gs::with_scene(scene, |scene| {
+        let id = scene.lights.len();
+        scene.lights.push(light);
+        id
+    })
+}
+
+#[cfg_attr(all(not(target_os = "wasi"), feature = "wasm-bindgen"), wasm_bindgen)]
+#[cfg_attr(feature = "pyo3", pyfunction)]
+#[no_mangle]
+pub extern "C" fn set_light_position(scene: usize, light: usize, pos: Vec3) {
+    gs::with_scene(scene, |scene| {
+        scene.lights[light].set_position(pos.into());
+    });
+}
+
+#[cfg_attr(all(not(target_os = "wasi"), feature = "wasm-bindgen"), wasm_bindgen)]
+#[cfg_attr(feature = "pyo3", pyfunction)]
+#[no_mangle]
+pub extern "C" fn set_light_direction(scene: usize, light: usize, dir: Vec3) {
+    gs::with_scene(scene, |scene| {
+        scene.lights[light].set_direction(dir.into());
+    });
+}
+
+#[cfg_attr(all(not(target_os = "wasi"), feature = "wasm-bindgen"), wasm_bindgen)]
+#[cfg_attr(feature = "pyo3", pyfunction)]
+#[no_mangle]
+pub extern "C" fn set_light_color(scene: usize, light: usize, col: Vec3) {
+    gs::with_scene(scene, |scene| {
+        scene.lights[light].set_color(col.into());
+    });
+}
+
+#[cfg_attr(all(not(target_os = "wasi"), feature = "wasm-bindgen"), wasm_bindgen)]
+#[cfg_attr(feature = "pyo3", pyfunction)]
+#[no_mangle]
+pub extern "C" fn clear_lights(scene: usize) {
+    gs::with_scene(scene, |scene| {
+        scene.lights.clear();
+    });
+}
+
+#[cfg_attr(all(not(target_os = "wasi"), feature = "wasm-bindgen"), wasm_bindgen)]
+#[cfg_attr(feature = "pyo3", pyfunction)]
+#[no_mangle]
+pub extern "C" fn set_blinn_phong(
+    scene: usize,
+    base_color: Vec3,
+    ambient: Vec3,
+    specular: Vec3,
+    shininess: f32,
+    emissive: Vec3,
+) {
+    let mut material = BlinnPhong::default();
+    material.base_color = base_color.into();
+    material.ambient = ambient.into();
+    material.specular = specular.into();
+    material.shininess = shininess;
+    material.emissive = emissive.into();
+    set_standard_material(
+        scene,
+        StandardMaterial::BlinnPhong,
+        MaterialDesc::BlinnPhong(material),
+    );
+}
+
+#[cfg_attr(all(not(target_os = "wasi"), feature = "wasm-bindgen"), wasm_bindgen)]
+#[cfg_attr(feature = "pyo3", pyfunction)]
+#[no_mangle]
+pub extern "C" fn set_toon(
+    scene: usize,
+    base_color: Vec3,
+    bands: u32,
+    rim_color: Vec3,
+    rim_width: f32,
+    outline_color: Vec3,
+    outline_width: f32,
+) {
+    let mut material = Toon::default();
+    material.base_color = base_color.into();
+    material.bands = bands;
+    material.rim = Some(Rim {
+        color: rim_color.into(),
+        width: rim_width,
+    })
+    .filter(|_| rim_width > 0.0);
+    material.outline = Some(Outline {
+        color: outline_color.into(),
+        width: outline_width,
+    })
+    .filter(|_| outline_width > 0.0);
+    set_standard_material(scene, StandardMaterial::Toon, MaterialDesc::Toon(material));
+}
+
+#[cfg_attr(all(not(target_os = "wasi"), feature = "wasm-bindgen"), wasm_bindgen)]
+#[cfg_attr(feature = "pyo3", pyfunction)]
+#[no_mangle]
+pub extern "C" fn set_glyph(scene: usize, glyph: u32, color: Vec3) {
+    let material = Glyph {
+        glyph: char::from_u32(glyph).unwrap_or('?'),
+        color: color.into(),
+        ..Default::default()
+    };
+    set_standard_material(
+        scene,
+        StandardMaterial::Glyph,
+        MaterialDesc::Glyph(material),
+    );
+}
+
+/// Replaces the description of a standard material slot of the scene.
+fn set_standard_material(scene: usize, material: StandardMaterial, desc: MaterialDesc) {
+    gs::with_scene(scene, |scene| {
+        scene.set_material(material as usize, desc);
+    });
+}
+
+#[cfg_attr(all(not(target_os = "wasi"), feature = "wasm-bindgen"), wasm_bindgen)]
+#[cfg_attr(feature = "pyo3", pyfunction)]
+#[no_mangle]
+pub fn set_texture(scene: usize, image: &[u8], bilinear: bool) -> bool {
+    let Ok(mut texture) = texture::Texture::read(image) else {
+        return false;
+    };
+
+    if bilinear {
+        texture.filter = texture::Filter::Bilinear;
+    }
+
+    let texture = Arc::new(texture);
+
+    gs::with_scene(scene, |scene| {
+        // Replace the image of the standard textured material, or give it a new one.
+        let slot = StandardMaterial::Textured as usize;
+        match scene.materials().get(slot) {
+            Some(&Some(MaterialDesc::Textured { texture: idx, .. }))
+                if idx < scene.textures().len() =>
+            {
+                scene.textures_mut()[idx] = texture;
+            }
+            Some(_) => {
+                let idx = scene.textures().len();
+                scene.textures_mut().push(texture);
+                scene.materials_mut()[slot] = Some(MaterialDesc::textured(idx));
+            }
+            None => (),
+        }
+    })
+    .is_some()
+}
+
+/// Adds a material, described in JSON, to the scene.
+///
+/// The description is a [`MaterialDesc`], for instance `{"type": "Toon", "bands": 4}`. Returns the
+/// material ID, to be used with [`set_obj_material`].
+#[cfg_attr(all(not(target_os = "wasi"), feature = "wasm-bindgen"), wasm_bindgen)]
+#[cfg_attr(feature = "pyo3", pyfunction)]
+#[no_mangle]
+pub fn add_material(scene: usize, desc: &str) -> Option<usize> {
+    let desc: MaterialDesc = serde_json::from_str(desc).ok()?;
+    gs::with_scene(scene, |scene| scene.add_material(desc)).flatten()
+}
+
+/// Sets a single parameter of a material.
+///
+/// `value` is JSON, for instance `[1.0, 0.5, 0.0]` for a color. Returns `false`, if the material
+/// does not have such parameter, or the value is invalid.
+#[cfg_attr(all(not(target_os = "wasi"), feature = "wasm-bindgen"), wasm_bindgen)]
+#[cfg_attr(feature = "pyo3", pyfunction)]
+#[no_mangle]
+pub fn set_material_param(scene: usize, material: usize, name: &str, value: &str) -> bool {
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(value) else {
+        return false;
+    };
+
+    gs::with_scene(scene, |scene| {
+        let Some(desc) = scene.material(material) else {
+            return false;
+        };
+
+        let Ok(serde_json::Value::Object(mut params)) = serde_json::to_value(desc) else {
+            return false;
+        };
+
+        match params.get_mut(name) {
+            Some(param) if name != "type" => *param = value,
+            _ => return false,
+        }
+
+        match serde_json::from_value(params.into()) {
+            Ok(desc) => scene.set_material(material, desc),
+            Err(_) => false,
+        }
+    })
+    .unwrap_or(false)
+}
+
+/// Removes a material from the scene.
+///
+/// Returns `false`, if the material does not exist, or is still used by an object.
+#[cfg_attr(all(not(target_os = "wasi"), feature = "wasm-bindgen"), wasm_bindgen)]
+#[cfg_attr(feature = "pyo3", pyfunction)]
+#[no_mangle]
+pub extern "C" fn remove_material(scene: usize, material: usize) -> bool {
+    gs::with_scene(scene, |scene| scene.remove_material(material).is_some()).unwrap_or(false)
+}
+
+/// Sets the material of an object.
+///
+/// Returns `false`, if the object or the material does not exist.
+#[cfg_attr(all(not(target_os = "wasi"), feature = "wasm-bindgen"), wasm_bindgen)]
+#[cfg_attr(feature = "pyo3", pyfunction)]
+#[no_mangle]
+pub extern "C" fn set_obj_material(scene: usize, obj: usize, material: usize) -> bool {
+    gs::with_scene(scene, |scene| {
+        if scene.material(material).is_none() {
+            return false;
+        }
+
+        match scene.objects.get_mut(obj) {
+            Some(obj) => {
+                obj.material = material;
+                true
+            }
+            None => false,
+        }
+    })
+    .unwrap_or(false)
+}
+
 #[cfg_attr(all(not(target_os = "wasi"), feature = "wasm-bindgen"), wasm_bindgen)]
 #[cfg_attr(feature = "pyo3", pyfunction)]
 #[no_mangle]
@@ -361,24 +801,37 @@ impl From<Vector3> for Vec3 {
 
 #[cfg_attr(all(not(target_os = "wasi"), feature = "wasm-bindgen"), wasm_bindgen)]
 #[cfg_attr(feature = "pyo3", pyclass)]
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Default)]
 #[repr(C)]
 pub struct RgbPixel {
     pub r: u8,
     pub g: u8,
     pub b: u8,
-    pub c: u8,
+    /// Text style bits of the fragment: `1` - bold, `2` - underline, `4` - blink.
+    pub style: u8,
+    /// Unicode code point of the character.
+    pub c: u32,
 }
 
 impl RgbPixel {
-    pub fn to_u32(self) -> u32 {
-        let Self { r, g, b, c } = self;
-        u32::from_le_bytes([r, g, b, c])
-    }
+    pub const BOLD: u8 = 1;
+    pub const UNDERLINE: u8 = 2;
+    pub const BLINK: u8 = 4;
+
+    fn style_bits(style: Style) -> u8 {
+        let mut bits = 0;
+
+        if style.bold {
+            bits |= Self::BOLD;
+        }
+        if style.underline {
+            bits |= Self::UNDERLINE;
+        }
+        if style.blink {
+            bits |= Self::BLINK;
+        }
 
-    pub fn from_u32(val: u32) -> Self {
-        let [r, g, b, c] = val.to_le_bytes();
-        Self { r, g, b, c }
+        bits
     }
 }
 
@@ -392,33 +845,59 @@ impl QuantizePixel for RgbPixel {
         x: usize,
         y: usize,
     ) -> Self {
-        let [r, g, b] = TermColor::quantize_color(params, inp, dithering, x, y).as_rgb();
+        let [r, g, b] = match TermColor::quantize_color(params, inp, dithering, x, y) {
+            TermColor::Col16(col) => params.palette.colors()[col.ansi_index()],
+            col => col.as_rgb(),
+        };
         Self {
             r,
             g,
             b,
-            c: u8::quantize_color(&(), inp, dithering, x, y),
+            style: 0,
+            c: char::quantize_color(&params.char_ramp(), inp, dithering, x, y) as u32,
+        }
+    }
+
+    fn quantize_fragment(
+        params: &Self::Params,
+        frag: &Fragment,
+        dithering: &impl Dithering,
+        x: usize,
+        y: usize,
+    ) -> Self {
+        Self {
+            style: Self::style_bits(frag.style),
+            c: char::quantize_fragment(&params.char_ramp(), frag, dithering, x, y) as u32,
+            ..Self::quantize_color(params, frag.color.xyz(), dithering, x, y)
         }
     }
 }
 
 impl PixelDarken for RgbPixel {
-    fn darken(&mut self) {
-        self.c.darken();
+    fn darken(&mut self, ramp: &CharRamp) {
+        let mut c = char::from_u32(self.c).unwrap_or(char::REPLACEMENT_CHARACTER);
+        c.darken(ramp);
 
         let Self { r, g, b, .. } = *self;
 
         let mut col = TermColor::from_rgb([r, g, b]);
-        col.darken();
+        col.darken(ramp);
         let [r, g, b] = col.as_rgb();
 
-        *self = Self { r, g, b, ..*self }
+        *self = Self {
+            r,
+            g,
+            b,
+            c: c as u32,
+            ..*self
+        }
     }
 }
 
 impl PixelText for RgbPixel {
     fn embed(&mut self, c: char) {
-        self.c.embed(c)
+        self.c = c as u32;
+        self.style = 0;
     }
 }
 
@@ -7,6 +7,7 @@ use crate::{
     dithering::XorShufDither,
     extra::{camera_controller::CameraController, ortho_proj, Ctx},
     material::*,
+    texture::Texture,
     *,
 };
 use core::cell::RefCell;
@@ -15,7 +16,6 @@ use rhai::{Dynamic, Engine, Scope, AST};
 use std::rc::Rc;
 use std::sync::Arc;
 
-#[derive(Default)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[serde(default)]
 pub struct Scene {
@@ -27,14 +27,171 @@ pub struct Scene {
     pub camera_controller: CameraController,
     pub objects: Vec<Object>,
     pub bg: Background,
+    pub lights: Vec<Light>,
+    materials: Vec<Option<MaterialDesc>>,
+    textures: Vec<Arc<Texture>>,
     pub dithering: XorShufDither,
     #[cfg(feature = "scripting")]
     pub script: Option<Arc<str>>,
     #[serde(skip)]
     pub frames: usize,
+    #[serde(skip)]
+    materials_gen: usize,
+    /// Scripts, compiled for the materials of the scene.
+    #[cfg(feature = "scripting")]
+    #[serde(skip)]
+    scripts: ScriptCache,
+}
+
+impl Default for Scene {
+    fn default() -> Self {
+        Self {
+            ctx: Default::default(),
+            camera: Default::default(),
+            camera_props: Default::default(),
+            camera_controller: Default::default(),
+            objects: Default::default(),
+            bg: Default::default(),
+            lights: vec![Light::default()],
+            materials: vec![
+                Some(MaterialDesc::Unlit),
+                Some(MaterialDesc::Diffuse(Default::default())),
+                Some(MaterialDesc::UiText),
+                Some(MaterialDesc::BlinnPhong(Default::default())),
+                Some(MaterialDesc::textured(0)),
+                Some(MaterialDesc::Toon(Default::default())),
+                Some(MaterialDesc::Hatching(Default::default())),
+                Some(MaterialDesc::Glyph(Default::default())),
+                Some(MaterialDesc::Procedural(Default::default())),
+                Some(MaterialDesc::Matcap {
+                    texture: None,
+                    gradient: Default::default(),
+                    shading: Default::default(),
+                }),
+                Some(MaterialDesc::ScalarMap(Default::default())),
+            ],
+            textures: vec![Arc::new(
+                Texture::from_fn(1, 1, |_, _| na::vector![1.0, 1.0, 1.0, 1.0]).unwrap(),
+            )],
+            dithering: Default::default(),
+            #[cfg(feature = "scripting")]
+            script: Default::default(),
+            frames: Default::default(),
+            materials_gen: 0,
+            #[cfg(feature = "scripting")]
+            scripts: Default::default(),
+        }
+    }
 }
 
 impl Scene {
+    /// Returns descriptions of the materials of the scene.
+    ///
+    /// Indices of this list are material IDs, referenced by [`Object::material`]. Removed
+    /// materials leave `None` in their slot, so that the IDs of other materials stay the same. By
+    /// default, the list contains every [`StandardMaterial`].
+    pub fn materials(&self) -> &[Option<MaterialDesc>] {
+        &self.materials
+    }
+
+    /// Returns mutable material descriptions.
+    ///
+    /// Materials get reinstantiated before the next render.
+    pub fn materials_mut(&mut self) -> &mut Vec<Option<MaterialDesc>> {
+        self.materials_gen += 1;
+        &mut self.materials
+    }
+
+    /// Returns description of material with given ID.
+    pub fn material(&self, id: usize) -> Option<&MaterialDesc> {
+        self.materials.get(id)?.as_ref()
+    }
+
+    /// Adds a material to the scene.
+    ///
+    /// Returns ID of the new material, reusing slots of removed materials. IDs of
+    /// [`StandardMaterial`]s are never reused, even if the materials were removed. Returns `None`,
+    /// if the description references a texture that does not exist.
+    pub fn add_material(&mut self, desc: MaterialDesc) -> Option<usize> {
+        self.instantiate(&desc)?;
+
+        let materials = self.materials_mut();
+
+        if materials.len() < STANDARD_MATERIALS {
+            materials.resize(STANDARD_MATERIALS, None);
+        }
+
+        let free = materials[STANDARD_MATERIALS..]
+            .iter()
+            .position(Option::is_none)
+            .map(|i| i + STANDARD_MATERIALS);
+
+        let id = if let Some(id) = free {
+            materials[id] = Some(desc);
+            id
+        } else {
+            materials.push(Some(desc));
+            materials.len() - 1
+        };
+
+        Some(id)
+    }
+
+    /// Replaces description of an existing material.
+    ///
+    /// Returns `false`, if there is no such material, or the description references a texture
+    /// that does not exist.
+    pub fn set_material(&mut self, id: usize, desc: MaterialDesc) -> bool {
+        if self.material(id).is_none() || self.instantiate(&desc).is_none() {
+            return false;
+        }
+
+        self.materials_mut()[id] = Some(desc);
+
+        true
+    }
+
+    /// Removes a material from the scene.
+    ///
+    /// Materials still used by objects can not be removed, in which case `None` is returned.
+    pub fn remove_material(&mut self, id: usize) -> Option<MaterialDesc> {
+        if self.objects.iter().any(|o| o.material == id) {
+            return None;
+        }
+
+        self.material(id)?;
+        self.materials_mut()[id].take()
+    }
+
+    /// Creates a material instance, sharing compiled scripts with the other scene materials.
+    fn instantiate(&mut self, desc: &MaterialDesc) -> Option<Box<dyn Material>> {
+        #[cfg(feature = "scripting")]
+        let material = desc.instantiate_cached(&self.textures, &mut self.scripts);
+        #[cfg(not(feature = "scripting"))]
+        let material = desc.instantiate(&self.textures);
+        material
+    }
+
+    /// Checks whether every object references an existing material.
+    pub fn validate_objects(&self) -> bool {
+        self.objects
+            .iter()
+            .all(|o| self.material(o.material).is_some())
+    }
+
+    /// Returns textures, referenced by the material descriptions.
+    pub fn textures(&self) -> &[Arc<Texture>] {
+        &self.textures
+    }
+
+    /// Returns mutable textures.
+    ///
+    /// Materials get reinstantiated before the next render.
+    pub fn textures_mut(&mut self) -> &mut Vec<Arc<Texture>> {
+        self.materials_gen += 1;
+        &mut self.textures
+    }
+
     fn update_camera(&mut self) {
         let proj = match self.camera_props.proj_mode {
             ProjectionMode::Perspective => na::Perspective3::new(
@@ -106,8 +263,19 @@ pub enum StandardMaterial {
     Unlit = 0,
     Diffuse = 1,
     UiText = 2,
+    BlinnPhong = 3,
+    Textured = 4,
+    Toon = 5,
+    Hatching = 6,
+    Glyph = 7,
+    Procedural = 8,
+    Matcap = 9,
+    ScalarMap = 10,
 }
 
+/// Number of [`StandardMaterial`]s, which occupy the first material IDs of every scene.
+const STANDARD_MATERIALS: usize = StandardMaterial::ScalarMap as usize + 1;
+
 struct SceneAux {
     #[cfg(feature = "scripting")]
     last_script: Option<Arc<str>>,
@@ -118,6 +286,9 @@ struct SceneAux {
     #[cfg(feature = "scripting")]
     state: Dynamic,
     start: Option<f64>,
+    materials: Vec<Box<dyn Material>>,
+    /// Generation of the scene materials, that `materials` were instantiated from.
+    materials_gen: Option<usize>,
 }
 
 impl SceneAux {
@@ -132,6 +303,8 @@ impl SceneAux {
             #[cfg(feature = "scripting")]
             state: Default::default(),
             start: None,
+            materials: vec![],
+            materials_gen: None,
         };
 
         ret.update_scripts(scene, scenes);
@@ -141,6 +314,32 @@ impl SceneAux {
 }
 
 impl SceneAux {
+    /// Instantiates scene materials, if they were modified.
+    ///
+    /// Materials with invalid texture references, and slots of removed materials are replaced
+    /// with [`Unlit`].
+    fn update_materials(&mut self, scene: &mut Scene) {
+        if self.materials_gen == Some(scene.materials_gen) {
+            return;
+        }
+
+        self.materials_gen = Some(scene.materials_gen);
+
+        let descs = core::mem::take(&mut scene.materials);
+        self.materials = descs
+            .iter()
+            .map(|desc| {
+                desc.as_ref()
+                    .and_then(|desc| scene.instantiate(desc))
+                    .unwrap_or_else(|| Box::<Unlit>::default())
+            })
+            .collect();
+        scene.materials = descs;
+
+        #[cfg(feature = "scripting")]
+        scene.scripts.prune();
+    }
+
     pub fn update_scripts(&mut self, scene: &mut Scene, scenes: &Scenes) {
         #[cfg(feature = "scripting")]
         if scene.script.as_ref().map(|v| v.as_ptr())
@@ -162,13 +361,6 @@ impl SceneAux {
 }
 
 thread_local! {
-    static MATERIALS: Rc<RefCell<Vec<Box<dyn Material + Send>>>> =
-        Rc::new(RefCell::new(vec![
-            Box::new(Unlit::default()) as Box<dyn Material + Send>,
-            Box::new(Diffuse::default()),
-            Box::new(UiText::default()),
-        ]))
-    ;
     static SCENES: Rc<RefCell<Scenes>> = {
         #[cfg(feature = "wasm")]
         crate::extra::bindings::set_panic_hook();
@@ -199,9 +391,6 @@ impl Default for Scenes {
 
                 engine
                     .set_strict_variables(true)
-                    .register_fn("sin", f32::sin)
-                    .register_fn("cos", f32::cos)
-                    .register_fn("atan2", f32::atan2)
                     .register_type::<SceneRef>()
                     .register_fn(
                         "set_transform",
@@ -221,6 +410,24 @@ impl Default for Scenes {
                     .register_fn("set_text", |s: SceneRef, obj: i64, text: &str| {
                         s.borrow_mut().objects[obj as usize].text = Some(text.into());
                     })
+                    .register_fn(
+                        "set_light_position",
+                        |s: SceneRef, light: i64, pos: Vector3| {
+                            s.borrow_mut().lights[light as usize].set_position(pos);
+                        },
+                    )
+                    .register_fn(
+                        "set_light_direction",
+                        |s: SceneRef, light: i64, dir: Vector3| {
+                            s.borrow_mut().lights[light as usize].set_direction(dir);
+                        },
+                    )
+                    .register_fn(
+                        "set_light_color",
+                        |s: SceneRef, light: i64, col: Vector3| {
+                            s.borrow_mut().lights[light as usize].set_color(col);
+                        },
+                    )
                     .register_set("camera_fov", |s: &mut SceneRef, fov: f32| {
                         s.borrow_mut().camera_props.fov = fov;
                     })
@@ -237,30 +444,6 @@ impl Default for Scenes {
                         },
                     )
                     .register_get("frames", |s: &mut SceneRef| s.borrow_mut().frames as i64)
-                    .register_type::<Vector4>()
-                    .register_fn("vec4", Vector4::new)
-                    .register_get("x", |v: &mut Vector4| v.x)
-                    .register_get("y", |v: &mut Vector4| v.y)
-                    .register_get("z", |v: &mut Vector4| v.z)
-                    .register_get("w", |v: &mut Vector4| v.w)
-                    .register_set("x", |v: &mut Vector4, x: f32| v.x = x)
-                    .register_set("y", |v: &mut Vector4, y: f32| v.y = y)
-                    .register_set("z", |v: &mut Vector4, z: f32| v.z = z)
-                    .register_set("w", |v: &mut Vector4, w: f32| v.w = w)
-                    .register_type::<Vector3>()
-                    .register_fn("vec3", Vector3::new)
-                    .register_get("x", |v: &mut Vector3| v.x)
-                    .register_get("y", |v: &mut Vector3| v.y)
-                    .register_get("z", |v: &mut Vector3| v.z)
-                    .register_set("x", |v: &mut Vector3, x: f32| v.x = x)
-                    .register_set("y", |v: &mut Vector3, y: f32| v.y = y)
-                    .register_set("z", |v: &mut Vector3, z: f32| v.z = z)
-                    .register_type::<Vector2>()
-                    .register_fn("vec2", Vector2::new)
-                    .register_get("x", |v: &mut Vector2| v.x)
-                    .register_get("y", |v: &mut Vector2| v.y)
-                    .register_set("x", |v: &mut Vector2, x: f32| v.x = x)
-                    .register_set("y", |v: &mut Vector2, y: f32| v.y = y)
                     .register_type::<Transform3>()
                     .register_type::<UnitQuaternion>()
                     .register_fn(
@@ -269,16 +452,14 @@ impl Default for Scenes {
                     )
                     .register_fn("create_transform", crate::extra::create_transform);
 
+                register_math(&mut engine);
+
                 engine
             },
         }
     }
 }
 
-fn get_materials() -> Rc<RefCell<Vec<Box<dyn Material + Send>>>> {
-    MATERIALS.with(Clone::clone)
-}
-
 fn get_renderer() -> Rc<RefCell<Renderer>> {
     RENDERER.with(Clone::clone)
 }
@@ -329,6 +510,7 @@ pub fn with_scene<T>(scene: usize, f: impl FnOnce(&mut Scene) -> T) -> Option<T>
 pub fn render<T: QuantizePixel + PixelText>(
     scene: usize,
     conv_params: &T::Params,
+    ramp: &CharRamp,
     buf: &mut Vec<T>,
     w: usize,
     h: usize,
@@ -340,16 +522,20 @@ pub fn render<T: QuantizePixel + PixelText>(
     let renderer = get_renderer();
     let mut renderer = renderer.borrow_mut();
 
-    #[cfg(feature = "scripting")]
-    {
+    let elapsed = {
         let aux = &mut *scene.1.borrow_mut();
 
-        let elapsed = if let Some(s) = aux.start {
+        if let Some(s) = aux.start {
             elapsed - s
         } else {
             aux.start = Some(elapsed);
             0.0
-        };
+        }
+    };
+
+    #[cfg(feature = "scripting")]
+    {
+        let aux = &mut *scene.1.borrow_mut();
 
         aux.update_scripts(&mut *scene.0.borrow_mut(), &scenes);
         if let Some(ast) = aux.compiled_script.as_ref() {
@@ -369,6 +555,7 @@ pub fn render<T: QuantizePixel + PixelText>(
         }
     }
 
+    let aux = &mut *scene.1.borrow_mut();
     let scene = &mut *scene.0.borrow_mut();
 
     scene.frames += 1;
@@ -378,17 +565,27 @@ pub fn render<T: QuantizePixel + PixelText>(
 
     renderer.clear_screen(&scene.bg, conv_params, &mut scene.dithering, buf, w, h);
 
-    let materials = get_materials();
-    let mut materials = materials.borrow_mut();
+    for obj in &mut scene.objects {
+        if let ObjType::Voxels(grid) = &mut obj.ty {
+            grid.update_mesh();
+        }
+    }
+
+    aux.update_materials(scene);
 
     renderer.render(
-        &scene.camera,
+        FrameInput {
+            camera: &scene.camera,
+            lights: &scene.lights,
+            time: elapsed as f32,
+            frame: scene.frames,
+        },
         conv_params,
-        &mut materials[..],
+        &mut aux.materials[..],
         &scene.objects,
         &mut scene.dithering,
         buf,
     );
 
-    renderer.text_pass(&scene.objects, buf);
+    renderer.text_pass(&scene.objects, ramp, buf);
 }
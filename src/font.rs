@@ -0,0 +1,575 @@
+//! Bitmap fonts, and character ramps calibrated to them.
+//!
+//! Characters of a [`CharRamp`] should get brighter at an even pace, which depends on the font
+//! the output is viewed with. [`BitmapFont::calibrate_ramp`] measures how much ink each glyph
+//! puts on screen, and picks characters, that are evenly spaced in perceived lightness.
+
+use crate::color::CharRamp;
+use alloc::{collections::BTreeMap, vec::Vec};
+
+/// Shape of a glyph, as seen from afar.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GlyphShape {
+    pub c: char,
+    /// Fraction of the cell covered by ink.
+    pub coverage: f32,
+    /// Coverage of the top left, top right, bottom left, and bottom right quarters of the cell.
+    pub quadrants: [f32; 4],
+}
+
+impl GlyphShape {
+    /// Returns how unevenly the ink is spread between the quarters of the cell.
+    pub fn unevenness(&self) -> f32 {
+        self.quadrants
+            .iter()
+            .map(|q| (q - self.coverage) * (q - self.coverage))
+            .sum::<f32>()
+            / 4.0
+    }
+}
+
+/// Character ramp, built by [`BitmapFont::calibrate_ramp`].
+#[derive(Clone, Debug)]
+pub struct CalibratedRamp {
+    pub ramp: CharRamp,
+    /// Shapes of the ramp characters, in the same order.
+    pub shapes: Vec<GlyphShape>,
+}
+
+/// Monospace bitmap font.
+///
+/// Glyphs are stored as rows of pixels, top row first, where `true` marks ink.
+#[derive(Clone, Debug)]
+pub struct BitmapFont {
+    width: usize,
+    height: usize,
+    glyphs: BTreeMap<char, Vec<bool>>,
+}
+
+impl BitmapFont {
+    /// Creates a font without glyphs, with given cell size.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            glyphs: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the built-in 8x13 font, covering printable ASCII characters.
+    ///
+    /// This is the public domain `fixed` font of the X Window System.
+    pub fn fixed_8x13() -> Self {
+        let mut font = Self::new(8, 13);
+
+        for (c, rows) in (' '..='~').zip(FIXED_8X13.iter()) {
+            let pixels = rows
+                .iter()
+                .flat_map(|row| (0..8).map(move |x| row & (0x80 >> x) != 0))
+                .collect();
+            font.insert(c, pixels);
+        }
+
+        font
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Sets the glyph of given character.
+    ///
+    /// Returns `false`, if the number of pixels does not match the cell size.
+    pub fn insert(&mut self, c: char, pixels: Vec<bool>) -> bool {
+        if pixels.len() != self.width * self.height {
+            return false;
+        }
+
+        self.glyphs.insert(c, pixels);
+        true
+    }
+
+    pub fn glyph(&self, c: char) -> Option<&[bool]> {
+        self.glyphs.get(&c).map(Vec::as_slice)
+    }
+
+    /// Returns all characters of the font, in ascending order.
+    pub fn chars(&self) -> impl Iterator<Item = char> + '_ {
+        self.glyphs.keys().copied()
+    }
+
+    /// Measures the shape of given character.
+    pub fn shape(&self, c: char) -> Option<GlyphShape> {
+        let glyph = self.glyph(c).filter(|glyph| !glyph.is_empty())?;
+
+        let mut ink = [0usize; 4];
+        let mut total = [0usize; 4];
+
+        for (i, &pixel) in glyph.iter().enumerate() {
+            let (x, y) = (i % self.width, i / self.width);
+            let quadrant = (y * 2 >= self.height) as usize * 2 + (x * 2 >= self.width) as usize;
+            total[quadrant] += 1;
+            ink[quadrant] += pixel as usize;
+        }
+
+        let coverage = ink.iter().sum::<usize>() as f32 / glyph.len() as f32;
+        // Quarters of cells, that are a single pixel wide or tall, are empty.
+        let quadrants = [0, 1, 2, 3].map(|i| {
+            if total[i] > 0 {
+                ink[i] as f32 / total[i] as f32
+            } else {
+                coverage
+            }
+        });
+
+        Some(GlyphShape {
+            c,
+            coverage,
+            quadrants,
+        })
+    }
+
+    /// Builds a ramp of up to `levels` characters, picked from `candidates`.
+    ///
+    /// Characters are picked so that their perceived lightness, when drawn as light ink on dark
+    /// background, is as evenly spaced as possible, between the emptiest and the fullest
+    /// candidate. When several characters are equally close, the one with ink spread most evenly
+    /// across the cell is chosen. Characters missing in the font are skipped.
+    ///
+    /// Returns `None`, if none of the candidates are in the font.
+    pub fn calibrate_ramp(
+        &self,
+        candidates: impl IntoIterator<Item = char>,
+        levels: usize,
+    ) -> Option<CalibratedRamp> {
+        let mut shapes: Vec<(f32, GlyphShape)> = candidates
+            .into_iter()
+            .filter_map(|c| self.shape(c))
+            .map(|shape| (lightness(shape.coverage), shape))
+            .collect();
+
+        shapes.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        let mut seen = alloc::collections::BTreeSet::new();
+        shapes.retain(|(_, shape)| seen.insert(shape.c));
+
+        let min = shapes.first()?.0;
+        let max = shapes.last()?.0;
+        let levels = levels.clamp(1, shapes.len());
+
+        let mut picked: Vec<GlyphShape> = Vec::with_capacity(levels);
+        let mut next = 0;
+
+        for level in 0..levels {
+            let target = if levels > 1 {
+                min + (max - min) * level as f32 / (levels - 1) as f32
+            } else {
+                min
+            };
+
+            // Leave enough characters for the remaining levels, so that the ramp stays strictly
+            // increasing.
+            let last = shapes.len() - (levels - level);
+            let (idx, _) =
+                shapes[next..=last]
+                    .iter()
+                    .enumerate()
+                    .min_by(|(_, (a, sa)), (_, (b, sb))| {
+                        libm::fabsf(a - target)
+                            .total_cmp(&libm::fabsf(b - target))
+                            .then(sa.unevenness().total_cmp(&sb.unevenness()))
+                    })?;
+
+            picked.push(shapes[next + idx].1);
+            next += idx + 1;
+        }
+
+        Some(CalibratedRamp {
+            ramp: CharRamp::new(picked.iter().map(|shape| shape.c))?,
+            shapes: picked,
+        })
+    }
+}
+
+/// Converts ink coverage (linear light) to CIE lightness, in `[0; 1]` range.
+fn lightness(coverage: f32) -> f32 {
+    let l = if coverage > 216.0 / 24389.0 {
+        116.0 * libm::cbrtf(coverage) - 16.0
+    } else {
+        coverage * 24389.0 / 27.0
+    };
+
+    l / 100.0
+}
+
+#[cfg(feature = "std")]
+mod io {
+    use super::*;
+    use std::io::{BufRead, Error, ErrorKind, Result};
+
+    /// Largest number of pixels in a glyph cell, accepted from BDF files.
+    const MAX_CELL_SIZE: usize = 1 << 16;
+
+    fn invalid(msg: impl Into<alloc::string::String>) -> Error {
+        Error::new(ErrorKind::InvalidData, msg.into())
+    }
+
+    fn numbers<const N: usize>(args: &str) -> Result<[i64; N]> {
+        let mut out = [0; N];
+        let mut args = args.split_whitespace();
+
+        for v in &mut out {
+            *v = args
+                .next()
+                .and_then(|v| v.parse().ok())
+                .ok_or_else(|| invalid("invalid BDF number"))?;
+        }
+
+        Ok(out)
+    }
+
+    impl BitmapFont {
+        /// Reads a font from a BDF file.
+        ///
+        /// Glyphs are placed within the font bounding box. Characters are taken from glyph
+        /// encodings, which are assumed to be Unicode code points, and glyphs without one are
+        /// skipped. Fonts with cells larger than 65536 pixels are rejected.
+        pub fn from_bdf(reader: impl BufRead) -> Result<Self> {
+            let mut font: Option<(Self, [i64; 4])> = None;
+            let mut encoding = None;
+            let mut bbx = [0; 4];
+            let mut lines = reader.lines();
+
+            while let Some(line) = lines.next() {
+                let line = line?;
+                let line = line.trim();
+                let (keyword, args) = line.split_once(' ').unwrap_or((line, ""));
+
+                match keyword {
+                    "FONTBOUNDINGBOX" => {
+                        let bounds = numbers::<4>(args)?;
+                        let (Ok(w), Ok(h)) =
+                            (usize::try_from(bounds[0]), usize::try_from(bounds[1]))
+                        else {
+                            return Err(invalid("invalid BDF bounding box"));
+                        };
+                        if w.checked_mul(h)
+                            .filter(|&size| size <= MAX_CELL_SIZE)
+                            .is_none()
+                        {
+                            return Err(invalid("BDF bounding box too large"));
+                        }
+                        font = Some((Self::new(w, h), bounds));
+                    }
+                    "ENCODING" => {
+                        let [code] = numbers::<1>(args)?;
+                        encoding = u32::try_from(code).ok().and_then(char::from_u32);
+                    }
+                    "BBX" => bbx = numbers::<4>(args)?,
+                    "BITMAP" => {
+                        let Some((font, [_, font_h, font_x, font_y])) = &mut font else {
+                            return Err(invalid("missing BDF bounding box"));
+                        };
+                        let [_, h, x, y] = bbx;
+
+                        // Top left corner of the glyph within the cell.
+                        let top = font_h
+                            .saturating_add(*font_y)
+                            .saturating_sub(h.saturating_add(y));
+                        let left = x.saturating_sub(*font_x);
+                        let (w, cell_h) = (font.width as i64, font.height as i64);
+                        let mut pixels = alloc::vec![false; font.width * font.height];
+
+                        for row in 0..h.max(0) {
+                            let line = lines
+                                .next()
+                                .ok_or_else(|| invalid("truncated BDF bitmap"))??;
+
+                            for (i, digit) in line.trim().chars().enumerate() {
+                                let bits = digit
+                                    .to_digit(16)
+                                    .ok_or_else(|| invalid("invalid BDF bitmap"))?;
+
+                                for bit in 0..4 {
+                                    let (px, py) = (
+                                        left.saturating_add((i * 4 + bit) as i64),
+                                        top.saturating_add(row),
+                                    );
+                                    let inside = (0..w).contains(&px) && (0..cell_h).contains(&py);
+
+                                    if inside && bits & (8 >> bit) != 0 {
+                                        pixels[(py * w + px) as usize] = true;
+                                    }
+                                }
+                            }
+                        }
+
+                        if let Some(c) = encoding.take() {
+                            font.insert(c, pixels);
+                        }
+                    }
+                    _ => (),
+                }
+            }
+
+            font.map(|(font, _)| font)
+                .ok_or_else(|| invalid("missing BDF bounding box"))
+        }
+    }
+}
+
+/// Glyphs of [`BitmapFont::fixed_8x13`], one byte per row, with the most significant bit on the
+/// left.
+#[rustfmt::skip]
+const FIXED_8X13: [[u8; 13]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+    [0x00, 0x00, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x00, 0x10, 0x00, 0x00], // '!'
+    [0x00, 0x00, 0x24, 0x24, 0x24, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // '"'
+    [0x00, 0x00, 0x00, 0x24, 0x24, 0x7e, 0x24, 0x7e, 0x24, 0x24, 0x00, 0x00, 0x00], // '#'
+    [0x00, 0x00, 0x10, 0x3c, 0x50, 0x50, 0x38, 0x14, 0x14, 0x78, 0x10, 0x00, 0x00], // '$'
+    [0x00, 0x00, 0x22, 0x52, 0x24, 0x08, 0x08, 0x10, 0x24, 0x2a, 0x44, 0x00, 0x00], // '%'
+    [0x00, 0x00, 0x00, 0x00, 0x30, 0x48, 0x48, 0x30, 0x4a, 0x44, 0x3a, 0x00, 0x00], // '&'
+    [0x00, 0x00, 0x10, 0x10, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // '\''
+    [0x00, 0x00, 0x04, 0x08, 0x08, 0x10, 0x10, 0x10, 0x08, 0x08, 0x04, 0x00, 0x00], // '('
+    [0x00, 0x00, 0x20, 0x10, 0x10, 0x08, 0x08, 0x08, 0x10, 0x10, 0x20, 0x00, 0x00], // ')'
+    [0x00, 0x00, 0x24, 0x18, 0x7e, 0x18, 0x24, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // '*'
+    [0x00, 0x00, 0x00, 0x00, 0x10, 0x10, 0x7c, 0x10, 0x10, 0x00, 0x00, 0x00, 0x00], // '+'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x38, 0x30, 0x40, 0x00], // ','
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x7c, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // '-'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x38, 0x10, 0x00], // '.'
+    [0x00, 0x00, 0x02, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0x80, 0x00, 0x00], // '/'
+    [0x00, 0x00, 0x18, 0x24, 0x42, 0x42, 0x42, 0x42, 0x42, 0x24, 0x18, 0x00, 0x00], // '0'
+    [0x00, 0x00, 0x10, 0x30, 0x50, 0x10, 0x10, 0x10, 0x10, 0x10, 0x7c, 0x00, 0x00], // '1'
+    [0x00, 0x00, 0x3c, 0x42, 0x42, 0x02, 0x04, 0x18, 0x20, 0x40, 0x7e, 0x00, 0x00], // '2'
+    [0x00, 0x00, 0x7e, 0x02, 0x04, 0x08, 0x1c, 0x02, 0x02, 0x42, 0x3c, 0x00, 0x00], // '3'
+    [0x00, 0x00, 0x04, 0x0c, 0x14, 0x24, 0x44, 0x44, 0x7e, 0x04, 0x04, 0x00, 0x00], // '4'
+    [0x00, 0x00, 0x7e, 0x40, 0x40, 0x5c, 0x62, 0x02, 0x02, 0x42, 0x3c, 0x00, 0x00], // '5'
+    [0x00, 0x00, 0x1c, 0x20, 0x40, 0x40, 0x5c, 0x62, 0x42, 0x42, 0x3c, 0x00, 0x00], // '6'
+    [0x00, 0x00, 0x7e, 0x02, 0x04, 0x08, 0x08, 0x10, 0x10, 0x20, 0x20, 0x00, 0x00], // '7'
+    [0x00, 0x00, 0x3c, 0x42, 0x42, 0x42, 0x3c, 0x42, 0x42, 0x42, 0x3c, 0x00, 0x00], // '8'
+    [0x00, 0x00, 0x3c, 0x42, 0x42, 0x46, 0x3a, 0x02, 0x02, 0x04, 0x38, 0x00, 0x00], // '9'
+    [0x00, 0x00, 0x00, 0x00, 0x10, 0x38, 0x10, 0x00, 0x00, 0x10, 0x38, 0x10, 0x00], // ':'
+    [0x00, 0x00, 0x00, 0x00, 0x10, 0x38, 0x10, 0x00, 0x00, 0x38, 0x30, 0x40, 0x00], // ';'
+    [0x00, 0x00, 0x02, 0x04, 0x08, 0x10, 0x20, 0x10, 0x08, 0x04, 0x02, 0x00, 0x00], // '<'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x7e, 0x00, 0x00, 0x7e, 0x00, 0x00, 0x00, 0x00], // '='
+    [0x00, 0x00, 0x40, 0x20, 0x10, 0x08, 0x04, 0x08, 0x10, 0x20, 0x40, 0x00, 0x00], // '>'
+    [0x00, 0x00, 0x3c, 0x42, 0x42, 0x02, 0x04, 0x08, 0x08, 0x00, 0x08, 0x00, 0x00], // '?'
+    [0x00, 0x00, 0x3c, 0x42, 0x42, 0x4e, 0x52, 0x56, 0x4a, 0x40, 0x3c, 0x00, 0x00], // '@'
+    [0x00, 0x00, 0x18, 0x24, 0x42, 0x42, 0x42, 0x7e, 0x42, 0x42, 0x42, 0x00, 0x00], // 'A'
+    [0x00, 0x00, 0x78, 0x44, 0x42, 0x44, 0x78, 0x44, 0x42, 0x44, 0x78, 0x00, 0x00], // 'B'
+    [0x00, 0x00, 0x3c, 0x42, 0x40, 0x40, 0x40, 0x40, 0x40, 0x42, 0x3c, 0x00, 0x00], // 'C'
+    [0x00, 0x00, 0x78, 0x44, 0x42, 0x42, 0x42, 0x42, 0x42, 0x44, 0x78, 0x00, 0x00], // 'D'
+    [0x00, 0x00, 0x7e, 0x40, 0x40, 0x40, 0x78, 0x40, 0x40, 0x40, 0x7e, 0x00, 0x00], // 'E'
+    [0x00, 0x00, 0x7e, 0x40, 0x40, 0x40, 0x78, 0x40, 0x40, 0x40, 0x40, 0x00, 0x00], // 'F'
+    [0x00, 0x00, 0x3c, 0x42, 0x40, 0x40, 0x40, 0x4e, 0x42, 0x46, 0x3a, 0x00, 0x00], // 'G'
+    [0x00, 0x00, 0x42, 0x42, 0x42, 0x42, 0x7e, 0x42, 0x42, 0x42, 0x42, 0x00, 0x00], // 'H'
+    [0x00, 0x00, 0x7c, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x7c, 0x00, 0x00], // 'I'
+    [0x00, 0x00, 0x1f, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x44, 0x38, 0x00, 0x00], // 'J'
+    [0x00, 0x00, 0x42, 0x44, 0x48, 0x50, 0x60, 0x50, 0x48, 0x44, 0x42, 0x00, 0x00], // 'K'
+    [0x00, 0x00, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x7e, 0x00, 0x00], // 'L'
+    [0x00, 0x00, 0x82, 0x82, 0xc6, 0xaa, 0x92, 0x92, 0x82, 0x82, 0x82, 0x00, 0x00], // 'M'
+    [0x00, 0x00, 0x42, 0x42, 0x62, 0x52, 0x4a, 0x46, 0x42, 0x42, 0x42, 0x00, 0x00], // 'N'
+    [0x00, 0x00, 0x3c, 0x42, 0x42, 0x42, 0x42, 0x42, 0x42, 0x42, 0x3c, 0x00, 0x00], // 'O'
+    [0x00, 0x00, 0x7c, 0x42, 0x42, 0x42, 0x7c, 0x40, 0x40, 0x40, 0x40, 0x00, 0x00], // 'P'
+    [0x00, 0x00, 0x3c, 0x42, 0x42, 0x42, 0x42, 0x42, 0x52, 0x4a, 0x3c, 0x02, 0x00], // 'Q'
+    [0x00, 0x00, 0x7c, 0x42, 0x42, 0x42, 0x7c, 0x50, 0x48, 0x44, 0x42, 0x00, 0x00], // 'R'
+    [0x00, 0x00, 0x3c, 0x42, 0x40, 0x40, 0x3c, 0x02, 0x02, 0x42, 0x3c, 0x00, 0x00], // 'S'
+    [0x00, 0x00, 0xfe, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x00, 0x00], // 'T'
+    [0x00, 0x00, 0x42, 0x42, 0x42, 0x42, 0x42, 0x42, 0x42, 0x42, 0x3c, 0x00, 0x00], // 'U'
+    [0x00, 0x00, 0x82, 0x82, 0x44, 0x44, 0x44, 0x28, 0x28, 0x28, 0x10, 0x00, 0x00], // 'V'
+    [0x00, 0x00, 0x82, 0x82, 0x82, 0x82, 0x92, 0x92, 0x92, 0xaa, 0x44, 0x00, 0x00], // 'W'
+    [0x00, 0x00, 0x82, 0x82, 0x44, 0x28, 0x10, 0x28, 0x44, 0x82, 0x82, 0x00, 0x00], // 'X'
+    [0x00, 0x00, 0x82, 0x82, 0x44, 0x28, 0x10, 0x10, 0x10, 0x10, 0x10, 0x00, 0x00], // 'Y'
+    [0x00, 0x00, 0x7e, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x40, 0x7e, 0x00, 0x00], // 'Z'
+    [0x00, 0x00, 0x3c, 0x20, 0x20, 0x20, 0x20, 0x20, 0x20, 0x20, 0x3c, 0x00, 0x00], // '['
+    [0x00, 0x00, 0x80, 0x80, 0x40, 0x20, 0x10, 0x08, 0x04, 0x02, 0x02, 0x00, 0x00], // '\\'
+    [0x00, 0x00, 0x78, 0x08, 0x08, 0x08, 0x08, 0x08, 0x08, 0x08, 0x78, 0x00, 0x00], // ']'
+    [0x00, 0x00, 0x10, 0x28, 0x44, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // '^'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xfe, 0x00], // '_'
+    [0x00, 0x10, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // '`'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x3c, 0x02, 0x3e, 0x42, 0x46, 0x3a, 0x00, 0x00], // 'a'
+    [0x00, 0x00, 0x40, 0x40, 0x40, 0x5c, 0x62, 0x42, 0x42, 0x62, 0x5c, 0x00, 0x00], // 'b'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x3c, 0x42, 0x40, 0x40, 0x42, 0x3c, 0x00, 0x00], // 'c'
+    [0x00, 0x00, 0x02, 0x02, 0x02, 0x3a, 0x46, 0x42, 0x42, 0x46, 0x3a, 0x00, 0x00], // 'd'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x3c, 0x42, 0x7e, 0x40, 0x42, 0x3c, 0x00, 0x00], // 'e'
+    [0x00, 0x00, 0x1c, 0x22, 0x20, 0x20, 0x7c, 0x20, 0x20, 0x20, 0x20, 0x00, 0x00], // 'f'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x3a, 0x44, 0x44, 0x38, 0x40, 0x3c, 0x42, 0x3c], // 'g'
+    [0x00, 0x00, 0x40, 0x40, 0x40, 0x5c, 0x62, 0x42, 0x42, 0x42, 0x42, 0x00, 0x00], // 'h'
+    [0x00, 0x00, 0x00, 0x10, 0x00, 0x30, 0x10, 0x10, 0x10, 0x10, 0x7c, 0x00, 0x00], // 'i'
+    [0x00, 0x00, 0x00, 0x04, 0x00, 0x0c, 0x04, 0x04, 0x04, 0x04, 0x44, 0x44, 0x38], // 'j'
+    [0x00, 0x00, 0x40, 0x40, 0x40, 0x44, 0x48, 0x70, 0x48, 0x44, 0x42, 0x00, 0x00], // 'k'
+    [0x00, 0x00, 0x30, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x7c, 0x00, 0x00], // 'l'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0xec, 0x92, 0x92, 0x92, 0x92, 0x82, 0x00, 0x00], // 'm'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x5c, 0x62, 0x42, 0x42, 0x42, 0x42, 0x00, 0x00], // 'n'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x3c, 0x42, 0x42, 0x42, 0x42, 0x3c, 0x00, 0x00], // 'o'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x5c, 0x62, 0x42, 0x62, 0x5c, 0x40, 0x40, 0x40], // 'p'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x3a, 0x46, 0x42, 0x46, 0x3a, 0x02, 0x02, 0x02], // 'q'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x5c, 0x22, 0x20, 0x20, 0x20, 0x20, 0x00, 0x00], // 'r'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x3c, 0x42, 0x30, 0x0c, 0x42, 0x3c, 0x00, 0x00], // 's'
+    [0x00, 0x00, 0x00, 0x20, 0x20, 0x7c, 0x20, 0x20, 0x20, 0x22, 0x1c, 0x00, 0x00], // 't'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x44, 0x44, 0x44, 0x44, 0x44, 0x3a, 0x00, 0x00], // 'u'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x44, 0x44, 0x44, 0x28, 0x28, 0x10, 0x00, 0x00], // 'v'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x82, 0x82, 0x92, 0x92, 0xaa, 0x44, 0x00, 0x00], // 'w'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x42, 0x24, 0x18, 0x18, 0x24, 0x42, 0x00, 0x00], // 'x'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x42, 0x42, 0x42, 0x46, 0x3a, 0x02, 0x42, 0x3c], // 'y'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x7e, 0x04, 0x08, 0x10, 0x20, 0x7e, 0x00, 0x00], // 'z'
+    [0x00, 0x00, 0x0e, 0x10, 0x10, 0x08, 0x30, 0x08, 0x10, 0x10, 0x0e, 0x00, 0x00], // '{'
+    [0x00, 0x00, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x00, 0x00], // '|'
+    [0x00, 0x00, 0x70, 0x08, 0x08, 0x10, 0x0c, 0x10, 0x08, 0x08, 0x70, 0x00, 0x00], // '}'
+    [0x00, 0x00, 0x24, 0x54, 0x48, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // '~'
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tiny_font() -> BitmapFont {
+        let mut font = BitmapFont::new(2, 2);
+        for (c, pixels) in [
+            (' ', [false, false, false, false]),
+            ('.', [true, false, false, false]),
+            (':', [true, false, true, false]),
+            ('#', [true, true, true, true]),
+        ] {
+            assert!(font.insert(c, pixels.to_vec()));
+        }
+        font
+    }
+
+    #[test]
+    fn shapes() {
+        let font = tiny_font();
+        assert!(!font.clone().insert('x', alloc::vec![true; 3]));
+
+        let dot = font.shape('.').unwrap();
+        assert_eq!(dot.coverage, 0.25);
+        assert_eq!(dot.quadrants, [1.0, 0.0, 0.0, 0.0]);
+        assert_eq!(dot.unevenness(), 0.1875);
+        assert_eq!(font.shape('#').unwrap().unevenness(), 0.0);
+        assert_eq!(font.shape('x'), None);
+    }
+
+    #[test]
+    fn calibrate_ramp() {
+        let font = tiny_font();
+
+        let ramp = font.calibrate_ramp("#:. x#".chars(), 3).unwrap();
+        assert_eq!(ramp.ramp.chars(), [' ', '.', '#']);
+        assert_eq!(
+            ramp.shapes.iter().map(|s| s.c).collect::<Vec<_>>(),
+            [' ', '.', '#']
+        );
+
+        // Levels are limited to the number of distinct candidates.
+        let ramp = font.calibrate_ramp("#:.  ".chars(), 10).unwrap();
+        assert_eq!(ramp.ramp.chars(), [' ', '.', ':', '#']);
+
+        let ramp = font.calibrate_ramp("#:. ".chars(), 0).unwrap();
+        assert_eq!(ramp.ramp.chars(), [' ']);
+
+        assert!(font.calibrate_ramp("xyz".chars(), 4).is_none());
+    }
+
+    #[test]
+    fn calibrate_fixed() {
+        let font = BitmapFont::fixed_8x13();
+        assert_eq!(font.chars().count(), 95);
+
+        let ramp = font.calibrate_ramp(font.chars(), 12).unwrap();
+        assert_eq!(ramp.ramp.chars().len(), 12);
+        assert_eq!(ramp.ramp.chars()[0], ' ');
+        assert!(ramp
+            .shapes
+            .windows(2)
+            .all(|w| w[0].coverage <= w[1].coverage && w[0].c != w[1].c));
+    }
+
+    #[cfg(feature = "std")]
+    mod bdf {
+        use super::*;
+        use std::io::{ErrorKind, Result};
+
+        fn bdf(bbox: &str, glyphs: &str) -> Result<BitmapFont> {
+            let data =
+                alloc::format!("STARTFONT 2.1\nFONTBOUNDINGBOX {bbox}\nCHARS 1\n{glyphs}ENDFONT\n");
+            BitmapFont::from_bdf(data.as_bytes())
+        }
+
+        fn glyph(encoding: i64, bbx: &str, rows: &str) -> alloc::string::String {
+            alloc::format!("STARTCHAR g\nENCODING {encoding}\nBBX {bbx}\nBITMAP\n{rows}ENDCHAR\n")
+        }
+
+        #[test]
+        fn placement() {
+            let glyphs = glyph(65, "2 2 1 0", "C0\n40\n") + glyph(-1, "1 1 0 0", "80\n").as_str();
+            let font = bdf("4 4 0 -1", &glyphs).unwrap();
+
+            assert_eq!((font.width(), font.height()), (4, 4));
+            // Glyphs without an encoding are skipped.
+            assert_eq!(font.chars().collect::<Vec<_>>(), ['A']);
+
+            let ink: Vec<_> = font
+                .glyph('A')
+                .unwrap()
+                .iter()
+                .enumerate()
+                .filter(|(_, &p)| p)
+                .map(|(i, _)| (i % 4, i / 4))
+                .collect();
+            assert_eq!(ink, [(1, 1), (2, 1), (2, 2)]);
+        }
+
+        #[test]
+        fn clipped_glyphs() {
+            // Glyphs outside of the cell, even at extreme offsets, are cut off.
+            for bbx in [
+                "8 1 -2 10",
+                "1 1 -9223372036854775808 9223372036854775807",
+                "1 1 9223372036854775807 -9223372036854775808",
+            ] {
+                let font = bdf("4 4 0 0", &glyph(66, bbx, "FF\n")).unwrap();
+                assert!(font.glyph('B').unwrap().iter().all(|&p| !p), "{bbx}");
+            }
+        }
+
+        #[test]
+        fn bounding_box_limits() {
+            assert!(bdf("256 256 0 0", "").is_ok());
+
+            for bbox in [
+                "65537 1 0 0",
+                "256 257 0 0",
+                "4294967296 4294967296 0 0",
+                "-1 4 0 0",
+                "4 4 0",
+            ] {
+                let err = bdf(bbox, "").unwrap_err();
+                assert_eq!(err.kind(), ErrorKind::InvalidData, "{bbox}");
+            }
+        }
+
+        #[test]
+        fn invalid() {
+            let err = BitmapFont::from_bdf(&b"STARTFONT 2.1\nENDFONT\n"[..]).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+            let data = alloc::format!("STARTFONT 2.1\n{}", glyph(65, "1 1 0 0", "80\n"));
+            let err = BitmapFont::from_bdf(data.as_bytes()).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+            for glyphs in [
+                glyph(65, "1 1 0 0", "G0\n"),
+                glyph(65, "1 x 0 0", "80\n"),
+                "ENCODING A\n".into(),
+                // Truncated bitmap.
+                "STARTCHAR A\nENCODING 65\nBBX 1 3 0 0\nBITMAP\n80\n".into(),
+            ] {
+                let err = bdf("4 4 0 0", &glyphs).unwrap_err();
+                assert_eq!(err.kind(), ErrorKind::InvalidData, "{glyphs:?}");
+            }
+        }
+    }
+}
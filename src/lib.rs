@@ -49,10 +49,20 @@ use nalgebra as na;
 pub mod dithering;
 use dithering::Dithering;
 pub mod color;
-use color::{PixelText, QuantizePixel};
+use color::{CharRamp, PixelText, QuantizePixel};
 pub mod extra;
 pub mod material;
 use material::*;
+pub mod mesh;
+use mesh::Mesh;
+pub mod voxel;
+use voxel::VoxelGrid;
+pub mod point_cloud;
+use point_cloud::PointCloud;
+pub mod light;
+use light::Light;
+pub mod font;
+pub mod texture;
 
 pub type Transform = na::Transform3<f32>;
 pub type Vector2 = na::Vector2<f32>;
@@ -126,20 +136,198 @@ pub fn term_char_aspect() -> (usize, usize) {
 /// Describes background parameters.
 ///
 /// These parameters need to be passed at the start of each frame, and are used to clear the
-/// background with specified color.
+/// background with specified color, or `backdrop`. They also apply to the following
+/// [`Renderer::render`] call, which blends objects towards the background color, if `fog` is set.
+#[derive(Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Background {
     pub color: Vector3,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub backdrop: Backdrop,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub fog: Option<Fog>,
 }
 
 impl Default for Background {
     fn default() -> Self {
         Self {
             color: Vector3::new(0.05, 0.23, 0.4),
+            backdrop: Backdrop::Solid,
+            fog: None,
         }
     }
 }
 
+/// What is drawn behind the objects.
+///
+/// Screen space backdrops are drawn when clearing the screen. Backdrops, that depend on the view
+/// direction ([`Backdrop::Sky`] and [`Backdrop::Starfield`]), need the camera, thus they are drawn
+/// by the first [`Renderer::render`] call after the clear, and stay fixed in the world as the
+/// camera rotates.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Backdrop {
+    /// Flat [`Background::color`].
+    #[default]
+    Solid,
+    /// Gradient from `top` to `bottom` of the screen.
+    VerticalGradient { top: Vector3, bottom: Vector3 },
+    /// Gradient from `inner` color at `center` of the screen (in `[0; 1]` range, from the top
+    /// left corner) to `outer` color at `radius` away from it (relative to the screen height).
+    RadialGradient {
+        center: Vector2,
+        radius: f32,
+        inner: Vector3,
+        outer: Vector3,
+    },
+    /// Sky, going from `horizon` to `zenith` color above the horizon (world XY plane), and quickly
+    /// fading to `ground` color below it.
+    Sky {
+        zenith: Vector3,
+        horizon: Vector3,
+        ground: Vector3,
+    },
+    /// Stars of given `color` over [`Background::color`]. `density` is the fraction of directions,
+    /// roughly one cell in size, that have a star.
+    Starfield { density: f32, color: Vector3 },
+    /// Image, stretched over the whole screen.
+    Image(Arc<texture::Texture>),
+    /// Nothing is drawn, and the buffer keeps its previous contents, which allows compositing
+    /// objects over existing content, or accumulating several renders. The buffer is only filled
+    /// with [`Background::color`], if its size does not match the screen.
+    ///
+    /// Use a buffer of [`Option`] pixels, filled with `None`, to tell which cells were left
+    /// untouched.
+    Transparent,
+}
+
+impl Backdrop {
+    /// Returns whether the backdrop is drawn by [`Renderer::render`], instead of
+    /// [`Renderer::clear_screen`].
+    pub fn is_view_dependent(&self) -> bool {
+        matches!(self, Self::Sky { .. } | Self::Starfield { .. })
+    }
+
+    /// Returns the color of a screen space backdrop at given screen cell.
+    ///
+    /// View dependent backdrops return `color`.
+    fn screen_color(&self, color: Vector3, x: usize, y: usize, w: usize, h: usize) -> Vector3 {
+        // Cell centers, in [0; 1] range.
+        let u = (x as f32 + 0.5) / w as f32;
+        let v = (y as f32 + 0.5) / h as f32;
+
+        match self {
+            Self::VerticalGradient { top, bottom } => top.lerp(bottom, v),
+            Self::RadialGradient {
+                center,
+                radius,
+                inner,
+                outer,
+            } => {
+                let (cw, ch) = term_char_aspect();
+                let dx = (u - center.x) * (w * cw) as f32 / (h * ch) as f32;
+                let dy = v - center.y;
+                let t = libm::sqrtf(dx * dx + dy * dy) / radius;
+                inner.lerp(outer, if t.is_nan() { 1.0 } else { t.min(1.0) })
+            }
+            Self::Image(texture) => texture.sample(Vector2::new(u, 1.0 - v)).xyz(),
+            Self::Solid | Self::Sky { .. } | Self::Starfield { .. } | Self::Transparent => color,
+        }
+    }
+
+    /// Returns the color of a view dependent backdrop in given world space direction.
+    ///
+    /// `star_scale` is the inverse of the angular size of a star. Screen space backdrops return
+    /// `color`.
+    fn view_color(&self, color: Vector3, dir: Vector3, star_scale: f32) -> Vector3 {
+        match self {
+            Self::Sky {
+                zenith,
+                horizon,
+                ground,
+            } => {
+                if dir.z >= 0.0 {
+                    horizon.lerp(zenith, libm::sqrtf(dir.z))
+                } else {
+                    horizon.lerp(ground, (-dir.z * 8.0).min(1.0))
+                }
+            }
+            Self::Starfield {
+                density,
+                color: star,
+            } => {
+                let cell = (dir * star_scale).map(|v| libm::floorf(v) as i32);
+                let h = hash(cell.x, cell.y, cell.z);
+                if ((h >> 8) as f32 / (1u32 << 24) as f32) < *density {
+                    // Vary the brightness, using bits not involved in the density check.
+                    let brightness = 0.4 + 0.6 * (h & 0xff) as f32 / 255.0;
+                    color.lerp(star, brightness)
+                } else {
+                    color
+                }
+            }
+            _ => color,
+        }
+    }
+}
+
+/// Depth fog.
+///
+/// Fog blends fragments of all materials towards the background color, based on their view depth
+/// (distance from the camera along its direction).
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Fog {
+    /// Fog, that linearly thickens from none at `start` depth to full at `end`.
+    Linear { start: f32, end: f32 },
+    /// Fog, that absorbs `density` fraction of the remaining light per unit of depth.
+    Exponential { density: f32 },
+    /// Like [`Fog::Exponential`], but with a clearer area close to the camera.
+    ExponentialSquared { density: f32 },
+}
+
+impl Fog {
+    /// Returns how much of the fog color covers an object at given view depth, in `[0; 1]` range.
+    pub fn amount(&self, depth: f32) -> f32 {
+        let depth = depth.max(0.0);
+
+        let amount = match *self {
+            Self::Linear { start, end } => {
+                if end > start {
+                    (depth - start) / (end - start)
+                } else if depth >= end {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            Self::Exponential { density } => 1.0 - libm::expf(-density * depth),
+            Self::ExponentialSquared { density } => {
+                let d = density * depth;
+                1.0 - libm::expf(-d * d)
+            }
+        };
+
+        amount.clamp(0.0, 1.0)
+    }
+}
+
+/// Per frame inputs of [`Renderer::render`].
+///
+/// Apart from the camera, these are passed on to materials through [`FrameContext`], together
+/// with the viewport.
+#[derive(Clone, Copy)]
+pub struct FrameInput<'a> {
+    /// Point of view to render from.
+    pub camera: &'a Camera,
+    /// Lights, that lit materials are shaded with.
+    pub lights: &'a [Light],
+    /// Time of the frame, in seconds.
+    pub time: f32,
+    /// Number of the frame.
+    pub frame: usize,
+}
+
 /// Immediate mode renderer.
 ///
 /// This object allows one to render graphics into arbitrary vectors, holding any [`QuantizePixel`]
@@ -180,13 +368,26 @@ struct PrimitiveId {
     pri_idx: usize,
 }
 
+/// Barycentric coordinates of primitive vertices.
+///
+/// Clipping may split a primitive into several smaller ones. Each of their vertices is described
+/// in terms of the vertices of the primitive that was returned by the material, so that fragments
+/// can be mapped back for attribute interpolation.
+type VertexBary = [Vector3; 3];
+
+const IDENTITY_BARY: VertexBary = [
+    Vector3::new(1.0, 0.0, 0.0),
+    Vector3::new(0.0, 1.0, 0.0),
+    Vector3::new(0.0, 0.0, 1.0),
+];
+
 #[derive(Default, Debug)]
 struct VertexState {
-    /// (Primitive, (mat_idx, obj_idx, pri_idx))
+    /// (Primitive, vertex barycentrics, (mat_idx, obj_idx, pri_idx))
     ///
     /// pri_idx will be passed to material at mat_idx, in order to shade fragments using correct
     /// material.
-    primitives: Vec<(Primitive, PrimitiveId)>,
+    primitives: Vec<(Primitive, VertexBary, PrimitiveId)>,
     /// Centers of objects in screen space coordinates
     ///
     /// This is not necessary per se, but it is used in text rendering to have stable centering of
@@ -194,10 +395,12 @@ struct VertexState {
     obj_clip_center: Vec<Vector4>,
 }
 
-fn plane_intersect(inside: Vector4, outside: Vector4, dim: usize, clip: f32) -> Vector4 {
+/// Intersects a segment with an axis aligned plane.
+///
+/// Returns the intersection point, and its interpolation factor between `inside` and `outside`.
+fn plane_intersect(inside: Vector4, outside: Vector4, dim: usize, clip: f32) -> (Vector4, f32) {
     let t = (clip - inside[dim]) / (outside[dim] - inside[dim]);
-    let ret = inside + t * (outside - inside);
-    ret
+    (inside + t * (outside - inside), t)
 }
 
 impl VertexState {
@@ -210,10 +413,18 @@ impl VertexState {
         match primitive {
             Primitive::Triangle(t) => self.clip_and_push_triangle(t, id),
             Primitive::Line(l) => self.clip_and_push_line(l, id),
+            Primitive::Point(p) => {
+                if p.pos.z / libm::fabsf(p.pos.w) >= 0.0 {
+                    self.primitives
+                        .push((Primitive::Point(p), IDENTITY_BARY, id));
+                }
+            }
         }
     }
 
     pub fn clip_and_push_line(&mut self, mut line: Line, id: PrimitiveId) {
+        let mut bary = IDENTITY_BARY;
+
         // TODO: clip the line on all axis, and both ends of the coord space.
         // Currently the lines may not render if none of the points is within the screen.
         for dim in 2..3 {
@@ -227,15 +438,13 @@ impl VertexState {
             } else if clip_cnt == 1 {
                 // 1 vertice clipped - find intersection point and push
                 if clip[0] {
-                    Line {
-                        start: plane_intersect(end, start, dim, 0.0),
-                        end,
-                    }
+                    let (start, t) = plane_intersect(end, start, dim, 0.0);
+                    bary[0] = bary[1].lerp(&bary[0], t);
+                    Line { start, end }
                 } else {
-                    Line {
-                        start,
-                        end: plane_intersect(start, end, dim, 0.0),
-                    }
+                    let (end, t) = plane_intersect(start, end, dim, 0.0);
+                    bary[1] = bary[0].lerp(&bary[1], t);
+                    Line { start, end }
                 }
             } else {
                 // All vertices clipped - don't push anything
@@ -243,7 +452,7 @@ impl VertexState {
             };
         }
 
-        self.primitives.push((Primitive::Line(line), id));
+        self.primitives.push((Primitive::Line(line), bary, id));
     }
 
     /// Performs near plane clipping and pushes the triangle on stack.
@@ -259,19 +468,19 @@ impl VertexState {
         if clip_cnt == 2 {
             // 2 verts clipped, we just bring all vertices to be within bounds
             let unclipped_idx = clip.iter().enumerate().find(|(_, v)| !**v).unwrap().0;
-            let mut cnt = 0;
             let verts = [a, b, c];
-            let [a, b, c] = verts.map(|v| {
-                let i = cnt;
-                cnt += 1;
-                if i == unclipped_idx {
-                    v
-                } else {
-                    plane_intersect(verts[unclipped_idx], v, dim, 0.0)
+            let mut bary = IDENTITY_BARY;
+            let mut out = verts;
+            for i in 0..3 {
+                if i != unclipped_idx {
+                    let (v, t) = plane_intersect(verts[unclipped_idx], verts[i], dim, 0.0);
+                    out[i] = v;
+                    bary[i] = IDENTITY_BARY[unclipped_idx].lerp(&IDENTITY_BARY[i], t);
                 }
-            });
+            }
+            let [a, b, c] = out;
             self.primitives
-                .push((Primitive::Triangle(Triangle { a, b, c }), id));
+                .push((Primitive::Triangle(Triangle { a, b, c }), bary, id));
         } else if clip_cnt == 1 {
             // 1 vert clipped, we get 2 intersection points, and create 2 triangles out of them
             let clipped_idx = clip.iter().enumerate().find(|(_, v)| **v).unwrap().0;
@@ -283,28 +492,35 @@ impl VertexState {
                 (0, 1 + (clipped_idx - 1) % 2)
             };
 
-            let c1 = plane_intersect(verts[i1], verts[clipped_idx], dim, 0.0);
-            let c2 = plane_intersect(verts[i2], verts[clipped_idx], dim, 0.0);
+            let (c1, t1) = plane_intersect(verts[i1], verts[clipped_idx], dim, 0.0);
+            let (c2, t2) = plane_intersect(verts[i2], verts[clipped_idx], dim, 0.0);
+            let b1 = IDENTITY_BARY[i1].lerp(&IDENTITY_BARY[clipped_idx], t1);
+            let b2 = IDENTITY_BARY[i2].lerp(&IDENTITY_BARY[clipped_idx], t2);
 
             {
                 let mut verts1 = verts;
+                let mut bary1 = IDENTITY_BARY;
                 verts1[clipped_idx] = c1;
+                bary1[clipped_idx] = b1;
                 let [a, b, c] = verts1;
                 self.primitives
-                    .push((Primitive::Triangle(Triangle { a, b, c }), id));
+                    .push((Primitive::Triangle(Triangle { a, b, c }), bary1, id));
             }
 
             {
                 let mut verts2 = verts;
+                let mut bary2 = IDENTITY_BARY;
                 verts2[i1] = c1;
+                bary2[i1] = b1;
                 verts2[clipped_idx] = c2;
+                bary2[clipped_idx] = b2;
                 let [a, b, c] = verts2;
                 self.primitives
-                    .push((Primitive::Triangle(Triangle { a, b, c }), id));
+                    .push((Primitive::Triangle(Triangle { a, b, c }), bary2, id));
             }
         } else if clip_cnt == 0 {
             self.primitives
-                .push((Primitive::Triangle(Triangle { a, b, c }), id));
+                .push((Primitive::Triangle(Triangle { a, b, c }), IDENTITY_BARY, id));
         }
     }
 }
@@ -318,6 +534,12 @@ struct RasterOutput {
 struct RasterState {
     w: usize,
     h: usize,
+    /// Fog and its color, set by the last screen clear.
+    fog: Option<(Fog, Vector3)>,
+    /// View dependent backdrop and its base color, left for the next render to draw.
+    backdrop: Option<(Backdrop, Vector3)>,
+    /// Inverse of the camera projection, used for recovering view depth.
+    inv_proj: Matrix4,
     /// Used for depth testing
     depth: Vec<f32>,
     /// Used as a stencil for text drawing
@@ -342,14 +564,21 @@ impl RasterState {
     ) {
         self.w = w;
         self.h = h;
+        self.fog = bg.fog.map(|fog| (fog, bg.color));
+        self.backdrop = Some((bg.backdrop.clone(), bg.color))
+            .filter(|(backdrop, _)| backdrop.is_view_dependent());
 
         let len = w * h;
-        buf.clear();
         dithering.new_frame(w, h);
 
-        for y in 0..h {
-            for x in 0..w {
-                buf.push(T::quantize_color(conv_params, bg.color, dithering, x, y));
+        if !matches!(bg.backdrop, Backdrop::Transparent) || buf.len() != len {
+            buf.clear();
+
+            for y in 0..h {
+                for x in 0..w {
+                    let color = bg.backdrop.screen_color(bg.color, x, y, w, h);
+                    buf.push(T::quantize_color(conv_params, color, dithering, x, y));
+                }
             }
         }
 
@@ -358,6 +587,44 @@ impl RasterState {
         self.objs.resize(len, !0usize);
     }
 
+    /// Draws the pending view dependent backdrop, given inverse of the view projection matrix.
+    fn draw_backdrop<T: QuantizePixel>(
+        &mut self,
+        inv_view_proj: &Matrix4,
+        conv_params: &T::Params,
+        dithering: &mut impl Dithering,
+        buf: &mut [T],
+    ) {
+        let Some((backdrop, color)) = self.backdrop.take() else {
+            return;
+        };
+
+        let (w, h) = (self.w, self.h);
+        let half_w = w.saturating_sub(1).max(1) as f32 / 2.0;
+        let half_h = h.saturating_sub(1).max(1) as f32 / 2.0;
+
+        let dir = |x: f32, y: f32| {
+            let ndc = |z| {
+                let p = inv_view_proj * Vector4::new(x / half_w - 1.0, 1.0 - y / half_h, z, 1.0);
+                p.xyz() / p.w
+            };
+            (ndc(1.0) - ndc(-1.0))
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_default()
+        };
+
+        // Size stars to roughly a cell, based on the angle between neighbouring columns, which are
+        // narrower than rows.
+        let star_scale = 1.0 / (dir(half_w, half_h) - dir(half_w + 1.0, half_h)).norm();
+
+        for y in 0..h {
+            for x in 0..w {
+                let color = backdrop.view_color(color, dir(x as f32, y as f32), star_scale);
+                buf[y * w + x] = T::quantize_color(conv_params, color, dithering, x, y);
+            }
+        }
+    }
+
     fn rasterize<T: QuantizePixel, M: Material + ?Sized>(
         &mut self,
         vs: &VertexState,
@@ -365,7 +632,7 @@ impl RasterState {
         max_obj: usize,
         conv_params: &T::Params,
         dithering: &mut impl Dithering,
-        buf: &mut Vec<T>,
+        buf: &mut [T],
     ) {
         let len = self.w * self.h;
         assert_eq!(buf.len(), len);
@@ -375,6 +642,7 @@ impl RasterState {
 
         for (
             p,
+            vert_bary,
             PrimitiveId {
                 mat_idx,
                 obj_idx,
@@ -384,7 +652,14 @@ impl RasterState {
         {
             let mat = mats[*mat_idx].as_mut();
 
-            let mut shade_pixel = |x, y, depth| {
+            // Maps perspective-correct weights of the clipped primitive to barycentric coordinates
+            // of the original one.
+            let to_bary = |weights: Vector3| {
+                let weights = weights / (weights.x + weights.y + weights.z);
+                vert_bary[0] * weights.x + vert_bary[1] * weights.y + vert_bary[2] * weights.z
+            };
+
+            let mut shade_pixel = |x, y, depth, bary| {
                 assert!(x < self.w);
                 assert!(y < self.h);
                 let bidx = y * self.w + x;
@@ -403,18 +678,31 @@ impl RasterState {
                 }
 
                 if depth >= 0.0 && self.depth[bidx] >= depth {
-                    if let Some(color) = mat.fragment_shade(
+                    if let Some(frag) = mat.fragment(
                         *pri_idx,
                         Vector2::new((x as f32) / self.w as f32, (y as f32) / self.h as f32),
                         depth,
+                        bary,
                     ) {
                         self.depth[bidx] = depth;
                         self.objs[bidx] = *obj_idx;
+
+                        let mut frag = frag;
+
+                        if let Some((fog, fog_color)) = self.fog {
+                            // Depth is the only thing we need, and it does not depend on the
+                            // screen position.
+                            let view =
+                                self.inv_proj * Vector4::new(0.0, 0.0, depth * 2.0 - 1.0, 1.0);
+                            let amount = fog.amount(-view.z / view.w);
+                            let color = frag.color.xyz().lerp(&fog_color, amount);
+                            frag.color = Vector4::new(color.x, color.y, color.z, frag.color.w);
+                        }
+
                         // Currently we only support cutout, although, that could be changed with
                         // RGB buffer rendering.
-                        if color.w >= 0.5 {
-                            buf[bidx] =
-                                T::quantize_color(conv_params, color.xyz(), dithering, x, y);
+                        if frag.color.w >= 0.5 {
+                            buf[bidx] = T::quantize_fragment(conv_params, &frag, dithering, x, y);
                         }
                     }
                 }
@@ -422,6 +710,7 @@ impl RasterState {
 
             match p {
                 Primitive::Triangle(t) => {
+                    let inv_w = [t.a, t.b, t.c].map(|v| 1.0 / v.w);
                     let t = [t.a, t.b, t.c]
                         .map(clip_to_ndc)
                         .map(|v| ndc_to_screen(v, self.w, self.h));
@@ -450,12 +739,18 @@ impl RasterState {
 
                             if wa >= 0.0 && wb >= 0.0 && wc >= 0.0 {
                                 let depth = wa * a.z + wb * b.z + wc * c.z;
-                                shade_pixel(x, y, depth);
+                                let bary = to_bary(Vector3::new(
+                                    wa * inv_w[0],
+                                    wb * inv_w[1],
+                                    wc * inv_w[2],
+                                ));
+                                shade_pixel(x, y, depth, bary);
                             }
                         }
                     }
                 }
                 Primitive::Line(l) => {
+                    let inv_w = [l.start, l.end].map(|v| 1.0 / v.w);
                     let l = [l.start, l.end]
                         .map(clip_to_ndc)
                         .map(|v| ndc_to_screen(v, self.w, self.h));
@@ -566,10 +861,31 @@ impl RasterState {
                             let total = da + db;
                             let lerp = da / total;
                             let depth = a.z + (b.z - a.z) * lerp;
-                            shade_pixel(x, y, depth);
+                            let bary = to_bary(Vector3::new(
+                                (1.0 - lerp) * inv_w[0],
+                                lerp * inv_w[1],
+                                0.0,
+                            ));
+                            shade_pixel(x, y, depth, bary);
                         },
                     );
                 }
+                Primitive::Point(p) => {
+                    let pos = ndc_to_screen(clip_to_ndc(p.pos), self.w, self.h);
+                    let size = libm::fmaxf(p.size, 1.0);
+                    let half = (size - 1.0) / 2.0;
+                    let min_x = libm::roundf(pos.x - half);
+                    let min_y = libm::roundf(pos.y - half);
+                    let max_x = libm::fminf(min_x + libm::roundf(size), self.w as f32);
+                    let max_y = libm::fminf(min_y + libm::roundf(size), self.h as f32);
+                    let bary = to_bary(Vector3::new(1.0, 0.0, 0.0));
+
+                    for y in (min_y.max(0.0) as usize)..(max_y.max(0.0) as usize) {
+                        for x in (min_x.max(0.0) as usize)..(max_x.max(0.0) as usize) {
+                            shade_pixel(x, y, pos.z, bary);
+                        }
+                    }
+                }
             }
         }
     }
@@ -628,6 +944,37 @@ pub struct Object {
 pub enum ObjType {
     Cube { size: Vector3 },
     Primitive(Primitive),
+    Mesh(Mesh),
+    Voxels(VoxelGrid),
+    PointCloud(PointCloud),
+}
+
+/// Computes normals and texture coordinates of a unit cube triangle.
+///
+/// Every face is mapped to the whole texture, upright when the face is viewed from the outside.
+/// Side faces have their up direction along +Z, while the top and bottom ones along +Y and -Y.
+fn cube_face_attrs(tri: [Vector3; 3]) -> VertexAttrs {
+    // The face axis is the one all vertices share a coordinate in.
+    let axis = (0..3)
+        .find(|&i| tri[0][i] == tri[1][i] && tri[0][i] == tri[2][i])
+        .unwrap_or(2);
+
+    let mut normal = Vector3::zeros();
+    normal[axis] = libm::copysignf(1.0, tri[0][axis]);
+
+    let up = if axis == 2 {
+        na::vector![0.0, normal.z, 0.0]
+    } else {
+        na::vector![0.0, 0.0, 1.0]
+    };
+    let right = up.cross(&normal);
+
+    VertexAttrs {
+        normals: Some([normal; 3]),
+        colors: None,
+        uvs: Some(tri.map(|p| Vector2::new(p.dot(&right) + 0.5, p.dot(&up) + 0.5))),
+        scalars: None,
+    }
 }
 
 impl ObjType {
@@ -669,16 +1016,18 @@ impl ObjType {
                     [0, 5, 4],
                 ];
 
-                for [a, b, c] in CUBE_INDICES.map(|v| {
-                    v.map(|v| {
-                        //println!("{:?} - {:?}", CUBE_VERTICES[v], CUBE_VERTICES[v].component_mul(&Vector4::new(size.x, size.y, size.z, 1.0)));
-                        //println!("{:?} - {:?}", mat * CUBE_VERTICES[v], mat * CUBE_VERTICES[v].component_mul(&Vector4::new(size.x, size.y, size.z, 1.0)));
+                for tri in CUBE_INDICES {
+                    let [a, b, c] = tri.map(|v| {
                         CUBE_VERTICES[v].component_mul(&Vector4::new(size.x, size.y, size.z, 1.0))
-                    })
-                }) {
+                    });
+                    let attrs = cube_face_attrs(tri.map(|v| CUBE_VERTICES[v].xyz()));
                     let triangle = Triangle { a, b, c };
-                    let (pri_idx, primitive) =
-                        material.primitive_shade(Primitive::Triangle(triangle), proj, model);
+                    let (pri_idx, primitive) = material.primitive_shade(
+                        Primitive::Triangle(triangle),
+                        &attrs,
+                        proj,
+                        model,
+                    );
                     state.clip_and_push_primitive(
                         primitive,
                         PrimitiveId {
@@ -690,7 +1039,8 @@ impl ObjType {
                 }
             }
             Self::Primitive(primitive) => {
-                let (pri_idx, primitive) = material.primitive_shade(*primitive, proj, model);
+                let (pri_idx, primitive) =
+                    material.primitive_shade(*primitive, &Default::default(), proj, model);
                 state.clip_and_push_primitive(
                     primitive,
                     PrimitiveId {
@@ -700,6 +1050,11 @@ impl ObjType {
                     },
                 );
             }
+            Self::Mesh(mesh) => mesh.gen(proj, model, state, material, obj_idx, mat_idx),
+            Self::Voxels(grid) => grid
+                .mesh()
+                .gen(proj, model, state, material, obj_idx, mat_idx),
+            Self::PointCloud(cloud) => cloud.gen(proj, model, state, material, obj_idx, mat_idx),
         }
     }
 }
@@ -710,6 +1065,27 @@ impl ObjType {
 pub enum Primitive {
     Triangle(Triangle),
     Line(Line),
+    Point(Point),
+}
+
+impl Primitive {
+    /// Transforms all vertices of the primitive by given matrix.
+    pub fn transform(&mut self, mat: &Matrix4) {
+        match self {
+            Self::Triangle(Triangle { a, b, c }) => {
+                *a = mat * *a;
+                *b = mat * *b;
+                *c = mat * *c;
+            }
+            Self::Line(Line { start, end }) => {
+                *start = mat * *start;
+                *end = mat * *end;
+            }
+            Self::Point(Point { pos, .. }) => {
+                *pos = mat * *pos;
+            }
+        }
+    }
 }
 
 /// A triangle.
@@ -724,6 +1100,49 @@ pub struct Triangle {
     pub c: Vector4,
 }
 
+/// A point.
+///
+/// Points are drawn as squares of `size` by `size` screen cells, that are centered around the
+/// projected position.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Point {
+    pub pos: Vector4,
+    pub size: f32,
+}
+
+impl Default for Point {
+    fn default() -> Self {
+        Self {
+            pos: Vector4::new(0.0, 0.0, 0.0, 1.0),
+            size: 1.0,
+        }
+    }
+}
+
+/// Optional per-vertex data of a primitive.
+///
+/// Values are stored in primitive vertex order - `a`, `b`, `c` for triangles, `start`, `end` for
+/// lines, and `pos` for points (the remaining elements are then unused). Materials may
+/// interpolate them during fragment shading using the barycentric coordinates passed to
+/// [`Material::fragment_shade`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VertexAttrs {
+    /// Model space vertex normals.
+    pub normals: Option<[Vector3; 3]>,
+    /// Vertex colors.
+    ///
+    /// Materials that support them use these as the base (albedo) color.
+    pub colors: Option<[Vector3; 3]>,
+    /// Texture coordinates.
+    pub uvs: Option<[Vector2; 3]>,
+    /// Scalar values, such as measurements of a surface plot.
+    ///
+    /// Materials that support them (such as [`ScalarMap`](material::ScalarMap)) map these to
+    /// colors.
+    pub scalars: Option<[f32; 3]>,
+}
+
 /// A line.
 ///
 /// Described rather oddly, in homogeneous coordinates, but oh well. Deal with it. Or if you don't
@@ -758,17 +1177,21 @@ impl Renderer {
 
     /// Draws objects on screen.
     ///
-    /// This function takes a list of objects, their materials, and draws them to given buffer.
-    /// Note that `buf` must be first cleared using [`Renderer::clear_screen`] function.
+    /// This function takes a list of objects, their materials, and draws them to given buffer,
+    /// viewed from the camera of `input`. Lights, time and frame number of `input` are passed on
+    /// to materials. Objects whose
+    /// [`Object::material`] index is out of range of `mats` are skipped. Note that `buf` must be
+    /// first cleared using [`Renderer::clear_screen`] function.
     pub fn render<T: QuantizePixel, M: Material + ?Sized>(
         &mut self,
-        camera: &Camera,
+        input: FrameInput,
         conv_params: &T::Params,
         mats: &mut [impl AsMut<M>],
         objects: &[Object],
         dithering: &mut impl Dithering,
-        buf: &mut Vec<T>,
+        buf: &mut [T],
     ) {
+        let camera = input.camera;
         let pos = camera.transform.transform_point(&Vector3::default().into());
         let dir = camera
             .transform
@@ -780,25 +1203,45 @@ impl Renderer {
         // First, split into view space triangles and lines, Sort of equivalent of vertex shading
         self.vertex_state.reset();
 
+        let ctx = FrameContext {
+            lights: input.lights,
+            time: input.time,
+            frame: input.frame,
+            camera_pos: pos.coords,
+            camera_dir: dir.try_normalize(f32::EPSILON).unwrap_or_default(),
+            viewport: (self.fragment_state.w, self.fragment_state.h),
+        };
+
         for mat in mats.iter_mut() {
-            mat.as_mut().new_frame();
+            mat.as_mut().new_frame(&ctx);
         }
 
         for (i, obj) in objects.iter().enumerate() {
             self.vertex_state
                 .obj_clip_center
                 .push(proj * obj.transform.matrix() * na::vector![0.0, 0.0, 0.0, 1.0]);
+            // Objects referencing materials that do not exist are not drawn.
+            let Some(mat) = mats.get_mut(obj.material) else {
+                continue;
+            };
             obj.ty.gen(
                 proj,
                 *obj.transform.matrix(),
                 &mut self.vertex_state,
-                mats[obj.material].as_mut(),
+                mat.as_mut(),
                 i,
                 obj.material,
             );
         }
 
         // Then, render into the buffer
+        self.fragment_state.draw_backdrop(
+            &proj.try_inverse().unwrap_or_default(),
+            conv_params,
+            dithering,
+            buf,
+        );
+        self.fragment_state.inv_proj = camera.proj.matrix().try_inverse().unwrap_or_default();
         self.fragment_state.rasterize(
             &self.vertex_state,
             mats,
@@ -812,11 +1255,13 @@ impl Renderer {
     /// Draws text on top of rendered objects.
     ///
     /// This function takes a list of objects (the identical set, to previously passed to
-    /// [`Renderer::render`]), and draws auxiliary text, if it was set.
+    /// [`Renderer::render`]), and draws auxiliary text, if it was set. Pixels around the text are
+    /// darkened within `ramp`, which should be the character ramp of the pixels.
     pub fn text_pass<T: PixelText + QuantizePixel>(
         &mut self,
         objects: &[Object],
-        buf: &mut Vec<T>,
+        ramp: &CharRamp,
+        buf: &mut [T],
     ) {
         for (i, obj) in objects.iter().enumerate() {
             let Some((min_x, min_y, mut max_x, mut max_y)) =
@@ -878,7 +1323,7 @@ impl Renderer {
                 let bidx = y * self.fragment_state.w + x;
                 // Do not darken other object pixels
                 if self.fragment_state.objs[bidx] == i {
-                    buf[bidx].darken();
+                    buf[bidx].darken(ramp);
                 }
             };
 
@@ -0,0 +1,182 @@
+//! Scene lights.
+
+use crate::Vector3;
+use nalgebra as na;
+
+/// Light source, illuminating lit materials.
+///
+/// Directions are in world space, and point the way light travels.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Light {
+    /// Infinitely distant light, such as the sun.
+    Directional { direction: Vector3, color: Vector3 },
+    /// Light emitted from a single point in all directions.
+    Point {
+        position: Vector3,
+        color: Vector3,
+        #[cfg_attr(feature = "serde", serde(default))]
+        attenuation: Attenuation,
+    },
+    /// Point light, limited to a cone.
+    ///
+    /// Light is at full intensity within `inner_angle` of the direction, and fades out towards
+    /// `outer_angle`. Both angles are in radians, measured from the center of the cone.
+    Spot {
+        position: Vector3,
+        direction: Vector3,
+        color: Vector3,
+        #[cfg_attr(feature = "serde", serde(default))]
+        attenuation: Attenuation,
+        inner_angle: f32,
+        outer_angle: f32,
+    },
+}
+
+impl Default for Light {
+    /// Returns the warm directional light, that used to be built into the lit materials.
+    fn default() -> Self {
+        Self::Directional {
+            direction: na::vector![0.5, 0.5, -0.5].normalize(),
+            color: na::vector![0.7, 0.4, 0.1] * 10.0,
+        }
+    }
+}
+
+impl Light {
+    pub fn position(&self) -> Option<Vector3> {
+        match self {
+            Self::Directional { .. } => None,
+            Self::Point { position, .. } | Self::Spot { position, .. } => Some(*position),
+        }
+    }
+
+    /// Moves the light to given position.
+    ///
+    /// Directional lights have no position, and are left unchanged.
+    pub fn set_position(&mut self, pos: Vector3) {
+        if let Self::Point { position, .. } | Self::Spot { position, .. } = self {
+            *position = pos;
+        }
+    }
+
+    pub fn direction(&self) -> Option<Vector3> {
+        match self {
+            Self::Point { .. } => None,
+            Self::Directional { direction, .. } | Self::Spot { direction, .. } => Some(*direction),
+        }
+    }
+
+    /// Points the light towards given direction.
+    ///
+    /// Point lights have no direction, and are left unchanged.
+    pub fn set_direction(&mut self, dir: Vector3) {
+        if let Self::Directional { direction, .. } | Self::Spot { direction, .. } = self {
+            *direction = dir;
+        }
+    }
+
+    pub fn color(&self) -> Vector3 {
+        match self {
+            Self::Directional { color, .. }
+            | Self::Point { color, .. }
+            | Self::Spot { color, .. } => *color,
+        }
+    }
+
+    pub fn set_color(&mut self, col: Vector3) {
+        match self {
+            Self::Directional { color, .. }
+            | Self::Point { color, .. }
+            | Self::Spot { color, .. } => *color = col,
+        }
+    }
+
+    /// Computes light arriving at given world space position.
+    ///
+    /// Returns the direction towards the light, and the light's color, scaled by attenuation.
+    /// Returns `None`, if the position is not lit.
+    pub fn illuminate(&self, pos: Vector3) -> Option<(Vector3, Vector3)> {
+        match *self {
+            Self::Directional { direction, color } => {
+                Some((-direction.try_normalize(f32::EPSILON)?, color))
+            }
+            Self::Point {
+                position,
+                color,
+                attenuation,
+            } => {
+                let (dir, dist) = to_light(position, pos)?;
+                Some((dir, color * attenuation.factor(dist)))
+            }
+            Self::Spot {
+                position,
+                direction,
+                color,
+                attenuation,
+                inner_angle,
+                outer_angle,
+            } => {
+                let (dir, dist) = to_light(position, pos)?;
+                let cos_theta = -dir.dot(&direction.try_normalize(f32::EPSILON)?);
+                let cos_inner = libm::cosf(inner_angle);
+                let cos_outer = libm::cosf(outer_angle);
+
+                let cone = if cos_inner - cos_outer > f32::EPSILON {
+                    let t = ((cos_theta - cos_outer) / (cos_inner - cos_outer)).clamp(0.0, 1.0);
+                    t * t * (3.0 - 2.0 * t)
+                } else if cos_theta >= cos_outer {
+                    1.0
+                } else {
+                    0.0
+                };
+
+                if cone > 0.0 {
+                    Some((dir, color * (cone * attenuation.factor(dist))))
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+/// Returns normalized direction and distance from `pos` to `light`.
+fn to_light(light: Vector3, pos: Vector3) -> Option<(Vector3, f32)> {
+    let delta = light - pos;
+    let dist = delta.norm();
+    Some((delta.try_normalize(f32::EPSILON)?, dist))
+}
+
+/// Distance falloff of point and spot lights.
+///
+/// Intensity is scaled by `1 / (constant + linear * d + quadratic * d^2)`.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Attenuation {
+    pub constant: f32,
+    pub linear: f32,
+    pub quadratic: f32,
+}
+
+impl Default for Attenuation {
+    /// Returns attenuation that fades out at around 50 units.
+    fn default() -> Self {
+        Self {
+            constant: 1.0,
+            linear: 0.09,
+            quadratic: 0.032,
+        }
+    }
+}
+
+impl Attenuation {
+    pub fn factor(&self, dist: f32) -> f32 {
+        let denom = self.constant + self.linear * dist + self.quadratic * dist * dist;
+        if denom > f32::EPSILON {
+            1.0 / denom
+        } else {
+            1.0
+        }
+    }
+}
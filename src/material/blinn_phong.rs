@@ -0,0 +1,115 @@
+use super::*;
+
+/// Blinn-Phong lighting shader.
+///
+/// Computes ambient, diffuse and specular lighting from the lights of the scene, with specular
+/// highlights depending on the position of the camera. Vertex colors, if present, are
+/// multiplied with `base_color`.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct BlinnPhong {
+    /// Albedo of the surface.
+    pub base_color: Vector3,
+    /// Light, that reaches the surface from all directions.
+    pub ambient: Vector3,
+    /// Color of specular highlights.
+    pub specular: Vector3,
+    /// Specular exponent - the higher, the smaller and sharper the highlights.
+    pub shininess: f32,
+    /// Light emitted by the surface itself.
+    pub emissive: Vector3,
+    pub shading: Shading,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    lights: Vec<Light>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    surfaces: Vec<Surface>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    eye: Option<(Matrix4, Eye)>,
+}
+
+impl Default for BlinnPhong {
+    fn default() -> Self {
+        Self {
+            base_color: na::vector![0.8, 0.8, 0.8],
+            ambient: na::vector![0.1, 0.13, 0.25] * 2.0,
+            specular: na::vector![1.0, 1.0, 1.0],
+            shininess: 32.0,
+            emissive: Default::default(),
+            shading: Shading::default(),
+            lights: alloc::vec![],
+            surfaces: alloc::vec![],
+            eye: None,
+        }
+    }
+}
+
+impl Material for BlinnPhong {
+    fn new_frame(&mut self, ctx: &FrameContext) {
+        self.surfaces.clear();
+        self.lights.clear();
+        self.lights.extend_from_slice(ctx.lights);
+    }
+
+    fn primitive_shade(
+        &mut self,
+        mut pri: Primitive,
+        attrs: &VertexAttrs,
+        proj: Matrix4,
+        model: Matrix4,
+    ) -> (usize, Primitive) {
+        if !matches!(self.eye, Some((p, _)) if p == proj) {
+            self.eye = Some((proj, Eye::from_proj(proj)));
+        }
+
+        let idx = self.surfaces.len();
+        self.surfaces
+            .push(Surface::new(&mut pri, attrs, self.shading, proj, model));
+        (idx, pri)
+    }
+
+    fn fragment_shade(
+        &self,
+        primitive: usize,
+        _pos: Vector2,
+        _: f32,
+        bary: Vector3,
+    ) -> Option<Vector4> {
+        let surface = &self.surfaces[primitive];
+        let normal = surface.normal(bary);
+        let albedo = match surface.color(bary) {
+            Some(color) => self.base_color.component_mul(&color),
+            None => self.base_color,
+        };
+
+        let pos = surface.position(bary);
+        let view_dir = self.eye.map(|(_, eye)| eye.view_dir(pos));
+
+        let mut diffuse = Vector3::default();
+        let mut specular = Vector3::default();
+
+        for (light_dir, light_col) in self.lights.iter().filter_map(|l| l.illuminate(pos)) {
+            let n_dot_l = normal.dot(&light_dir);
+
+            if n_dot_l <= 0.0 {
+                continue;
+            }
+
+            diffuse += light_col * n_dot_l;
+
+            if let Some(view_dir) = view_dir {
+                let half = (view_dir + light_dir)
+                    .try_normalize(f32::EPSILON)
+                    .unwrap_or_default();
+                let n_dot_h = libm::fmaxf(0.0, normal.dot(&half));
+                specular +=
+                    light_col.component_mul(&self.specular) * libm::powf(n_dot_h, self.shininess);
+            }
+        }
+
+        let color = (self.ambient + diffuse).component_mul(&albedo) + specular + self.emissive;
+        let color = tonemap(color);
+
+        Some(na::vector![color.x, color.y, color.z, 1.0])
+    }
+}
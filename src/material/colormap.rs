@@ -0,0 +1,274 @@
+use super::*;
+use crate::color::{PixelText, QuantizePixel};
+use crate::dithering::Dithering;
+
+/// Named mapping from scalar values in `[0; 1]` range to colors.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Colormap {
+    /// Perceptually uniform map from dark blue, through green, to yellow.
+    #[default]
+    Viridis,
+    /// Perceptually uniform map from black, through purple and red, to light yellow.
+    Magma,
+    /// Diverging map from blue, through light gray, to red.
+    Coolwarm,
+    /// Map from black to white.
+    Grayscale,
+}
+
+impl Colormap {
+    /// Returns the color of given value, which is clamped to `[0; 1]` range.
+    pub fn color(&self, t: f32) -> Vector3 {
+        let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+
+        let color = match self {
+            // Polynomial fits of the matplotlib colormaps.
+            Self::Viridis => polynomial(
+                [
+                    [0.277_727_33, 0.005_407_344_5, 0.334_099_8],
+                    [0.105_093_04, 1.404_613_5, 1.384_590_1],
+                    [-0.330_861_83, 0.214_847_56, 0.095_095_16],
+                    [-4.634_230_6, -5.799_101, -19.332_441],
+                    [6.228_27, 14.179_933, 56.690_55],
+                    [4.776_385, -13.745_145, -65.353_03],
+                    [-5.435_456, 4.645_852_6, 26.312_435],
+                ],
+                t,
+            ),
+            Self::Magma => polynomial(
+                [
+                    [-0.002_136_485, -0.000_749_655, -0.005_386_128],
+                    [0.251_660_54, 0.677_523_24, 2.494_026_6],
+                    [8.353_717, -3.577_719_5, 0.314_467_9],
+                    [-27.668_733, 14.264_731, -13.649_213],
+                    [52.176_14, -27.943_606, 12.944_169],
+                    [-50.768_524, 29.046_583, 4.234_153],
+                    [18.655_705, -11.489_773, -5.601_961_5],
+                ],
+                t,
+            ),
+            Self::Coolwarm => {
+                const POINTS: [[f32; 3]; 5] = [
+                    [0.230, 0.299, 0.754],
+                    [0.554, 0.690, 0.996],
+                    [0.865, 0.865, 0.865],
+                    [0.957, 0.598, 0.477],
+                    [0.706, 0.016, 0.150],
+                ];
+                let pos = t * (POINTS.len() - 1) as f32;
+                let i = core::cmp::min(pos as usize, POINTS.len() - 2);
+                Vector3::from(POINTS[i]).lerp(&Vector3::from(POINTS[i + 1]), pos - i as f32)
+            }
+            Self::Grayscale => Vector3::repeat(t),
+        };
+
+        color.map(|c| c.clamp(0.0, 1.0))
+    }
+}
+
+/// Evaluates a polynomial with given per-channel coefficients, lowest degree first.
+fn polynomial(coefs: [[f32; 3]; 7], t: f32) -> Vector3 {
+    coefs
+        .iter()
+        .rev()
+        .fold(Vector3::zeros(), |acc, c| acc * t + Vector3::from(*c))
+}
+
+/// Material, that colors surfaces by a scalar value.
+///
+/// Interpolated [`VertexAttrs::scalars`] are normalized from `min`-`max` range, and mapped through
+/// `colormap`. Primitives without scalars use their world space height (Z coordinate) instead,
+/// which is handy for surface plots. When `lit` is set, the surface is additionally shaded by the
+/// lights of the scene, similarly to [`Diffuse`].
+///
+/// See [`ColorBar`] for drawing a matching legend.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ScalarMap {
+    pub colormap: Colormap,
+    pub min: f32,
+    pub max: f32,
+    pub lit: bool,
+    /// Light, that reaches the surface from all directions, when `lit` is set.
+    pub ambient: Vector3,
+    pub shading: Shading,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    lights: Vec<Light>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    surfaces: Vec<(Surface, Option<[f32; 3]>)>,
+}
+
+impl Default for ScalarMap {
+    fn default() -> Self {
+        Self {
+            colormap: Colormap::default(),
+            min: 0.0,
+            max: 1.0,
+            lit: false,
+            ambient: default_ambient(),
+            shading: Shading::default(),
+            lights: alloc::vec![],
+            surfaces: alloc::vec![],
+        }
+    }
+}
+
+impl ScalarMap {
+    pub fn new(colormap: Colormap, min: f32, max: f32) -> Self {
+        Self {
+            colormap,
+            min,
+            max,
+            ..Default::default()
+        }
+    }
+
+    /// Returns a legend of this material, of given size, at given screen position.
+    pub fn color_bar(&self, x: usize, y: usize, width: usize, height: usize) -> ColorBar {
+        ColorBar {
+            colormap: self.colormap,
+            min: self.min,
+            max: self.max,
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+}
+
+impl Material for ScalarMap {
+    fn new_frame(&mut self, ctx: &FrameContext) {
+        self.surfaces.clear();
+        self.lights.clear();
+        self.lights.extend_from_slice(ctx.lights);
+    }
+
+    fn primitive_shade(
+        &mut self,
+        mut pri: Primitive,
+        attrs: &VertexAttrs,
+        proj: Matrix4,
+        model: Matrix4,
+    ) -> (usize, Primitive) {
+        let idx = self.surfaces.len();
+        let surface = Surface::new(&mut pri, attrs, self.shading, proj, model);
+        self.surfaces.push((surface, attrs.scalars));
+        (idx, pri)
+    }
+
+    fn fragment_shade(
+        &self,
+        primitive: usize,
+        _pos: Vector2,
+        _: f32,
+        bary: Vector3,
+    ) -> Option<Vector4> {
+        let (surface, scalars) = &self.surfaces[primitive];
+
+        let value = match scalars {
+            Some(scalars) => interpolate(scalars, bary),
+            None => surface.position(bary).z,
+        };
+
+        let mut color = self
+            .colormap
+            .color((value - self.min) / (self.max - self.min));
+
+        if self.lit {
+            let light = surface.diffuse(&self.lights, self.ambient, bary);
+            color = tonemap(color.component_mul(&light));
+        }
+
+        Some(na::vector![color.x, color.y, color.z, 1.0])
+    }
+}
+
+/// Legend of a [`Colormap`], drawn directly into the screen buffer.
+///
+/// The bar runs along its longer side - bottom to top when vertical, left to right when
+/// horizontal. `min` and `max` labels are placed next to the respective ends of the bar, to the
+/// right of vertical bars, and below horizontal ones.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ColorBar {
+    pub colormap: Colormap,
+    pub min: f32,
+    pub max: f32,
+    /// Screen column of the top left corner.
+    pub x: usize,
+    /// Screen row of the top left corner.
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl ColorBar {
+    /// Draws the legend into `buf`, that is `w` cells wide.
+    ///
+    /// This should be done after rendering the scene, and the parts outside the buffer are
+    /// clipped.
+    pub fn draw<T: QuantizePixel + PixelText>(
+        &self,
+        conv_params: &T::Params,
+        dithering: &impl Dithering,
+        buf: &mut [T],
+        w: usize,
+    ) {
+        if w == 0 {
+            return;
+        }
+
+        let h = buf.len() / w;
+        let vertical = self.height > self.width;
+        let len = if vertical { self.height } else { self.width };
+
+        for dy in 0..self.height {
+            for dx in 0..self.width {
+                let (x, y) = (self.x + dx, self.y + dy);
+
+                if x >= w || y >= h {
+                    continue;
+                }
+
+                let step = if vertical { self.height - 1 - dy } else { dx };
+                let t = step as f32 / core::cmp::max(len - 1, 1) as f32;
+                let color = self.colormap.color(t);
+                buf[y * w + x] = T::quantize_color(conv_params, color, dithering, x, y);
+            }
+        }
+
+        let mut label = |value: f32, x: usize, y: usize, right_align: bool| {
+            let text = alloc::format!("{value:.2}");
+            let len = text.chars().count();
+            let x = if right_align {
+                (x + 1).saturating_sub(len)
+            } else {
+                x
+            };
+
+            if y >= h {
+                return;
+            }
+
+            for (i, c) in text.chars().enumerate() {
+                if x + i < w {
+                    buf[y * w + x + i].embed(c);
+                }
+            }
+        };
+
+        let right = self.x + self.width;
+        let bottom = self.y + self.height;
+
+        if vertical {
+            label(self.max, right + 1, self.y, false);
+            label(self.min, right + 1, bottom - 1, false);
+        } else {
+            label(self.min, self.x, bottom, false);
+            label(self.max, right.saturating_sub(1), bottom, true);
+        }
+    }
+}
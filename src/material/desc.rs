@@ -0,0 +1,134 @@
+use super::*;
+use crate::texture::Texture;
+use alloc::boxed::Box;
+
+/// Serializable description of a material.
+///
+/// Descriptions hold only the parameters of materials, which allows scenes to store their
+/// materials as plain data, and instantiate them when loaded. Parameters, that are missing when
+/// deserializing, take their default values.
+///
+/// Textures are referenced by index into a list of textures, passed to
+/// [`MaterialDesc::instantiate`], so that multiple materials may share the same image.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type"))]
+pub enum MaterialDesc {
+    Unlit,
+    Diffuse(Diffuse),
+    UiText,
+    BlinnPhong(BlinnPhong),
+    Textured {
+        texture: usize,
+        #[cfg_attr(feature = "serde", serde(default))]
+        lit: bool,
+        #[cfg_attr(feature = "serde", serde(default = "default_ambient"))]
+        ambient: Vector3,
+        #[cfg_attr(feature = "serde", serde(default))]
+        shading: Shading,
+    },
+    Toon(Toon),
+    Hatching(Hatching),
+    Glyph(Glyph),
+    Procedural(Procedural),
+    ScalarMap(ScalarMap),
+    Matcap {
+        #[cfg_attr(feature = "serde", serde(default))]
+        texture: Option<usize>,
+        #[cfg_attr(feature = "serde", serde(default))]
+        gradient: MatcapGradient,
+        #[cfg_attr(feature = "serde", serde(default))]
+        shading: Shading,
+    },
+    #[cfg(feature = "scripting")]
+    Script {
+        source: Arc<str>,
+        #[cfg_attr(feature = "serde", serde(default))]
+        uniforms: alloc::collections::BTreeMap<alloc::string::String, Uniform>,
+        #[cfg_attr(feature = "serde", serde(default))]
+        shading: Shading,
+    },
+}
+
+impl MaterialDesc {
+    /// Describes an unlit [`Textured`] material with default parameters.
+    pub fn textured(texture: usize) -> Self {
+        Self::Textured {
+            texture,
+            lit: false,
+            ambient: default_ambient(),
+            shading: Shading::default(),
+        }
+    }
+
+    /// Creates a material instance from the description.
+    ///
+    /// Returns `None`, if the description references a texture out of range of `textures`, or
+    /// contains a script that fails to compile.
+    pub fn instantiate(&self, textures: &[Arc<Texture>]) -> Option<Box<dyn Material>> {
+        Some(match self {
+            Self::Unlit => Box::new(Unlit::default()),
+            Self::Diffuse(m) => Box::new(m.clone()),
+            Self::UiText => Box::new(UiText::default()),
+            Self::BlinnPhong(m) => Box::new(m.clone()),
+            Self::Textured {
+                texture,
+                lit,
+                ambient,
+                shading,
+            } => {
+                let mut m = Textured::new(textures.get(*texture)?.clone());
+                m.lit = *lit;
+                m.ambient = *ambient;
+                m.shading = *shading;
+                Box::new(m)
+            }
+            Self::Toon(m) => Box::new(m.clone()),
+            Self::Hatching(m) => Box::new(m.clone()),
+            Self::Glyph(m) => Box::new(m.clone()),
+            Self::Procedural(m) => Box::new(m.clone()),
+            Self::ScalarMap(m) => Box::new(m.clone()),
+            Self::Matcap {
+                texture,
+                gradient,
+                shading,
+            } => {
+                let texture = match texture {
+                    Some(texture) => Some(textures.get(*texture)?.clone()),
+                    None => None,
+                };
+                let mut m = Matcap::new(texture);
+                m.gradient = *gradient;
+                m.shading = *shading;
+                Box::new(m)
+            }
+            #[cfg(feature = "scripting")]
+            Self::Script { .. } => {
+                return self.instantiate_cached(textures, &mut ScriptCache::default())
+            }
+        })
+    }
+
+    /// Creates a material instance, like [`MaterialDesc::instantiate`], reusing scripts compiled
+    /// through `scripts`.
+    #[cfg(feature = "scripting")]
+    pub fn instantiate_cached(
+        &self,
+        textures: &[Arc<Texture>],
+        scripts: &mut ScriptCache,
+    ) -> Option<Box<dyn Material>> {
+        match self {
+            Self::Script {
+                source,
+                uniforms,
+                shading,
+            } => {
+                let mut m = ScriptMaterial::with_cache(source.clone(), scripts).ok()?;
+                m.uniforms = uniforms.clone();
+                m.shading = *shading;
+                Some(Box::new(m))
+            }
+            _ => self.instantiate(textures),
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use super::*;
+
+/// Unlit material, that draws every fragment with the same character.
+///
+/// Useful for markers and labels, such as rendering a face as the letter `X`, or a blinking
+/// warning sign. The style is only visible with [`Styled`](crate::color::Styled) pixels.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Glyph {
+    pub glyph: char,
+    pub color: Vector3,
+    pub style: Style,
+}
+
+impl Default for Glyph {
+    fn default() -> Self {
+        Self {
+            glyph: '#',
+            color: na::vector![1.0, 1.0, 1.0],
+            style: Style::default(),
+        }
+    }
+}
+
+impl Material for Glyph {
+    fn new_frame(&mut self, _: &FrameContext) {}
+
+    fn primitive_shade(
+        &mut self,
+        mut pri: Primitive,
+        _: &VertexAttrs,
+        proj: Matrix4,
+        model: Matrix4,
+    ) -> (usize, Primitive) {
+        pri.transform(&(proj * model));
+        (0, pri)
+    }
+
+    fn fragment_shade(&self, _: usize, _pos: Vector2, _: f32, _: Vector3) -> Option<Vector4> {
+        Some(na::vector![self.color.x, self.color.y, self.color.z, 1.0])
+    }
+
+    fn fragment(
+        &self,
+        primitive: usize,
+        pos: Vector2,
+        depth: f32,
+        bary: Vector3,
+    ) -> Option<Fragment> {
+        self.fragment_shade(primitive, pos, depth, bary)
+            .map(|color| Fragment {
+                color,
+                glyph: Some(self.glyph),
+                style: self.style,
+            })
+    }
+}
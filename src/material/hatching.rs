@@ -0,0 +1,152 @@
+use super::*;
+
+/// Pen-and-ink shading material.
+///
+/// Instead of relying on the density ramp of color quantization, this picks hatching characters
+/// by the amount of light reaching the surface:
+///
+/// - Fully lit surfaces are left blank.
+/// - Partially lit ones get single strokes (`/` or `\`), running across the direction light falls
+///   on the surface, as seen on screen.
+/// - Darker ones are cross-hatched (`X`).
+/// - The darkest ones are filled with `#`.
+///
+/// `levels` holds the light intensity thresholds (`0-1`) between `#`, `X`, single strokes, and
+/// blank space. Fragments are colored with `ink`. Primitives without normals (lines and points)
+/// do not pick characters, and are quantized as usual.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Hatching {
+    pub ink: Vector3,
+    /// Light, that reaches the surface from all directions.
+    pub ambient: Vector3,
+    pub levels: [f32; 3],
+    pub shading: Shading,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    lights: Vec<Light>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    surfaces: Vec<Surface>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    proj: Matrix4,
+}
+
+impl Default for Hatching {
+    fn default() -> Self {
+        Self {
+            ink: na::vector![1.0, 1.0, 1.0],
+            ambient: Default::default(),
+            levels: [0.15, 0.45, 0.7],
+            shading: Shading::default(),
+            lights: alloc::vec![],
+            surfaces: alloc::vec![],
+            proj: Matrix4::identity(),
+        }
+    }
+}
+
+impl Hatching {
+    /// Picks the stroke character for the fragment at world space `pos`.
+    ///
+    /// Strokes run perpendicular to the light direction projected onto the surface, which makes
+    /// them follow the contours of equal brightness.
+    fn stroke(&self, pos: Vector3, normal: Vector3, light_dir: Vector3) -> char {
+        let tangent = light_dir - normal * normal.dot(&light_dir);
+        let stroke = normal.cross(&tangent);
+
+        let to_ndc = |p: Vector3| {
+            let clip = self.proj * Vector4::new(p.x, p.y, p.z, 1.0);
+            clip.xy() / clip.w
+        };
+
+        // NDC Y axis points up, thus, positive slopes are drawn with forward slashes.
+        let delta = to_ndc(pos + stroke * 1e-2) - to_ndc(pos);
+
+        if delta.x * delta.y >= 0.0 {
+            '/'
+        } else {
+            '\\'
+        }
+    }
+}
+
+impl Material for Hatching {
+    fn new_frame(&mut self, ctx: &FrameContext) {
+        self.surfaces.clear();
+        self.lights.clear();
+        self.lights.extend_from_slice(ctx.lights);
+    }
+
+    fn primitive_shade(
+        &mut self,
+        mut pri: Primitive,
+        attrs: &VertexAttrs,
+        proj: Matrix4,
+        model: Matrix4,
+    ) -> (usize, Primitive) {
+        self.proj = proj;
+        let idx = self.surfaces.len();
+        self.surfaces
+            .push(Surface::new(&mut pri, attrs, self.shading, proj, model));
+        (idx, pri)
+    }
+
+    fn fragment_shade(&self, _: usize, _pos: Vector2, _: f32, _: Vector3) -> Option<Vector4> {
+        Some(na::vector![self.ink.x, self.ink.y, self.ink.z, 1.0])
+    }
+
+    fn fragment(
+        &self,
+        primitive: usize,
+        pos: Vector2,
+        depth: f32,
+        bary: Vector3,
+    ) -> Option<Fragment> {
+        let mut frag = Fragment::from(self.fragment_shade(primitive, pos, depth, bary)?);
+
+        let surface = &self.surfaces[primitive];
+        let normal = surface.normal(bary);
+
+        if normal == Vector3::zeros() {
+            return Some(frag);
+        }
+
+        let pos = surface.position(bary);
+
+        // The strongest light determines stroke direction.
+        let mut strongest = None;
+        let mut light = self.ambient;
+
+        for (light_dir, light_col) in self.lights.iter().filter_map(|l| l.illuminate(pos)) {
+            let contrib = light_col * libm::fmaxf(0.0, normal.dot(&light_dir));
+            let weight = luminance(contrib);
+
+            if !matches!(strongest, Some((w, _)) if w >= weight) {
+                strongest = Some((weight, light_dir));
+            }
+
+            light += contrib;
+        }
+
+        let intensity = luminance(tonemap(light));
+
+        frag.glyph = Some(if intensity < self.levels[0] {
+            '#'
+        } else if intensity < self.levels[1] {
+            'X'
+        } else if intensity < self.levels[2] {
+            match strongest {
+                Some((_, light_dir)) => self.stroke(pos, normal, light_dir),
+                None => '/',
+            }
+        } else {
+            ' '
+        });
+
+        Some(frag)
+    }
+}
+
+fn luminance(color: Vector3) -> f32 {
+    color.dot(&na::vector![0.21, 0.72, 0.07])
+}
@@ -0,0 +1,118 @@
+use super::*;
+use crate::texture::Texture;
+
+/// Procedural lit sphere, used by [`Matcap`] materials without a texture.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct MatcapGradient {
+    /// Color of surfaces facing up.
+    pub top: Vector3,
+    /// Color of surfaces facing down.
+    pub bottom: Vector3,
+    /// Color of surfaces facing sideways, away from the viewer.
+    pub rim: Vector3,
+}
+
+impl Default for MatcapGradient {
+    fn default() -> Self {
+        Self {
+            top: na::vector![0.95, 0.9, 0.8],
+            bottom: na::vector![0.1, 0.12, 0.25],
+            rim: na::vector![0.6, 0.75, 1.0],
+        }
+    }
+}
+
+impl MatcapGradient {
+    /// Returns the color of the sphere at given view space normal.
+    pub fn sample(&self, normal: Vector3) -> Vector3 {
+        let color = self.bottom.lerp(&self.top, normal.y * 0.5 + 0.5);
+        let rim = 1.0 - libm::fmaxf(normal.z, 0.0);
+        color.lerp(&self.rim, rim * rim * rim)
+    }
+}
+
+/// Material capture (lit sphere) material.
+///
+/// Colors the surface by looking up its view space normal in an image of a lit sphere, which
+/// gives convincing shading without any lights. The sphere is sampled from `texture`, which is
+/// fully covered by the sphere, or generated from `gradient` when there is no texture. Vertex
+/// colors, if present, tint the result.
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Matcap {
+    pub texture: Option<Arc<Texture>>,
+    pub gradient: MatcapGradient,
+    pub shading: Shading,
+    /// Rows of the world to view space rotation.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    view: [Vector3; 3],
+    #[cfg_attr(feature = "serde", serde(skip))]
+    surfaces: Vec<Surface>,
+}
+
+impl Matcap {
+    pub fn new(texture: Option<Arc<Texture>>) -> Self {
+        Self {
+            texture,
+            ..Default::default()
+        }
+    }
+}
+
+impl Material for Matcap {
+    fn new_frame(&mut self, ctx: &FrameContext) {
+        self.surfaces.clear();
+
+        // Same basis as the view matrix of the renderer, which keeps world Z pointing up.
+        let forward = ctx.camera_dir;
+        let right = forward
+            .cross(&na::vector![0.0, 0.0, 1.0])
+            .try_normalize(f32::EPSILON)
+            .unwrap_or(na::vector![1.0, 0.0, 0.0]);
+        let up = right.cross(&forward);
+        self.view = [right, up, -forward];
+    }
+
+    fn primitive_shade(
+        &mut self,
+        mut pri: Primitive,
+        attrs: &VertexAttrs,
+        proj: Matrix4,
+        model: Matrix4,
+    ) -> (usize, Primitive) {
+        let idx = self.surfaces.len();
+        self.surfaces
+            .push(Surface::new(&mut pri, attrs, self.shading, proj, model));
+        (idx, pri)
+    }
+
+    fn fragment_shade(
+        &self,
+        primitive: usize,
+        _pos: Vector2,
+        _: f32,
+        bary: Vector3,
+    ) -> Option<Vector4> {
+        let surface = &self.surfaces[primitive];
+        let normal = surface.normal(bary);
+        let [right, up, back] = self.view;
+        let normal = na::vector![normal.dot(&right), normal.dot(&up), normal.dot(&back)];
+
+        let mut color = match &self.texture {
+            // Stay clear of the edges, which would wrap around on repeating textures.
+            Some(texture) => texture
+                .sample(normal.xy() * 0.49 + na::vector![0.5, 0.5])
+                .xyz(),
+            None => self.gradient.sample(normal),
+        };
+
+        if let Some(tint) = surface.color(bary) {
+            color.component_mul_assign(&tint);
+        }
+
+        Some(na::vector![color.x, color.y, color.z, 1.0])
+    }
+}
@@ -0,0 +1,456 @@
+use super::*;
+pub use crate::color::{Fragment, Style};
+
+mod blinn_phong;
+pub use blinn_phong::BlinnPhong;
+mod textured;
+pub use textured::Textured;
+mod toon;
+pub use toon::{Outline, Rim, Toon};
+mod hatching;
+pub use hatching::Hatching;
+mod glyph;
+pub use glyph::Glyph;
+mod procedural;
+pub(crate) use procedural::hash;
+pub use procedural::{Pattern, Procedural, Space};
+mod matcap;
+pub use matcap::{Matcap, MatcapGradient};
+mod colormap;
+pub use colormap::{ColorBar, Colormap, ScalarMap};
+mod desc;
+pub use desc::MaterialDesc;
+#[cfg(feature = "scripting")]
+mod script;
+#[cfg(all(feature = "scripting", feature = "global-state"))]
+pub(crate) use script::register_math;
+#[cfg(feature = "scripting")]
+pub use script::{ScriptCache, ScriptMaterial, Uniform};
+
+/// Data shared by all materials throughout a frame.
+///
+/// Materials, that need any of this during shading, should copy it out in
+/// [`Material::new_frame`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FrameContext<'a> {
+    /// Lights of the scene.
+    pub lights: &'a [Light],
+    /// Time of the frame, in seconds.
+    pub time: f32,
+    /// Number of the frame.
+    pub frame: usize,
+    /// World space position of the camera.
+    pub camera_pos: Vector3,
+    /// World space direction the camera is looking at.
+    pub camera_dir: Vector3,
+    /// Width and height of the screen, in cells.
+    pub viewport: (usize, usize),
+}
+
+/// Defines a material and its shading.
+///
+/// Types that implement this are usually stateful, because instances of `Material` are the ones
+/// responsible for storing per-primitive data, used for fragment shading.
+pub trait Material {
+    /// Indicates the start of new frame.
+    ///
+    /// On new frame, all primitives are discarded, therefore, the material should clear any stored
+    /// data upon this call. Materials that need per-frame data, such as lights, should copy it out
+    /// of `ctx`.
+    fn new_frame(&mut self, ctx: &FrameContext);
+
+    /// Transforms and registers a primitive.
+    ///
+    /// This function takes a primitive (line/triangle), performs computation, and returns an ID,
+    /// associated with it. The ID will then later be used to call [`Material::fragment_shade`]
+    /// with.
+    ///
+    /// This structure allows materials to store arbitrary data for fragment shading purposes.
+    /// Optional per-vertex data of the primitive is passed in `attrs`.
+    fn primitive_shade(
+        &mut self,
+        primitive: Primitive,
+        attrs: &VertexAttrs,
+        proj: Matrix4,
+        model: Matrix4,
+    ) -> (usize, Primitive);
+
+    /// Shade a primitive at specified position.
+    ///
+    /// Material shall assume that provided position lies within the primitive.
+    ///
+    /// `bary` holds perspective-correct barycentric coordinates of the fragment, relative to the
+    /// vertices of the primitive returned by [`Material::primitive_shade`]. Use [`interpolate`] to
+    /// blend per-vertex data with them.
+    fn fragment_shade(
+        &self,
+        primitive: usize,
+        pos: Vector2,
+        depth: f32,
+        bary: Vector3,
+    ) -> Option<Vector4>;
+
+    /// Shade a primitive at specified position, producing a complete fragment.
+    ///
+    /// This is what the renderer calls. By default, it wraps the color of
+    /// [`Material::fragment_shade`]. Materials that pick output characters or text attributes
+    /// themselves (such as [`Hatching`] and [`Glyph`]) override this.
+    fn fragment(
+        &self,
+        primitive: usize,
+        pos: Vector2,
+        depth: f32,
+        bary: Vector3,
+    ) -> Option<Fragment> {
+        self.fragment_shade(primitive, pos, depth, bary)
+            .map(Fragment::from)
+    }
+}
+
+/// Interpolates per-vertex values with barycentric coordinates.
+pub fn interpolate<T>(values: &[T; 3], bary: Vector3) -> T
+where
+    T: Copy + core::ops::Mul<f32, Output = T> + core::ops::Add<Output = T>,
+{
+    values[0] * bary.x + values[1] * bary.y + values[2] * bary.z
+}
+
+/// Computes the matrix that transforms model space normals into world space.
+fn normal_matrix(model: Matrix4) -> na::Matrix3<f32> {
+    let m = model.fixed_view::<3, 3>(0, 0).into_owned();
+    m.try_inverse().unwrap_or(m).transpose()
+}
+
+/// World space surface data of a primitive, used by lit materials.
+#[derive(Clone, Copy, Default)]
+struct Surface {
+    positions: [Vector3; 3],
+    normals: [Vector3; 3],
+    colors: Option<[Vector3; 3]>,
+    uvs: Option<[Vector2; 3]>,
+}
+
+impl Surface {
+    /// Transforms the primitive into clip space, and records its world space surface data.
+    ///
+    /// Only triangles have normals, the ones of other primitives are left zeroed.
+    fn new(
+        pri: &mut Primitive,
+        attrs: &VertexAttrs,
+        shading: Shading,
+        proj: Matrix4,
+        model: Matrix4,
+    ) -> Self {
+        pri.transform(&model);
+
+        let (positions, normals) = match pri {
+            Primitive::Triangle(Triangle { a, b, c }) => {
+                let normals = match (shading, attrs.normals) {
+                    (Shading::Smooth, Some(normals)) => {
+                        let normal_mat = normal_matrix(model);
+                        normals.map(|n| (normal_mat * n).normalize())
+                    }
+                    _ => {
+                        let e1 = b.xyz() - a.xyz();
+                        let e2 = c.xyz() - a.xyz();
+                        [e1.cross(&e2)
+                            .try_normalize(f32::EPSILON)
+                            .unwrap_or_default(); 3]
+                    }
+                };
+
+                ([a.xyz(), b.xyz(), c.xyz()], normals)
+            }
+            Primitive::Line(Line { start, end }) => {
+                ([start.xyz(), end.xyz(), end.xyz()], Default::default())
+            }
+            Primitive::Point(Point { pos, .. }) => ([pos.xyz(); 3], Default::default()),
+        };
+
+        pri.transform(&proj);
+
+        Self {
+            positions,
+            normals,
+            colors: attrs.colors,
+            uvs: attrs.uvs,
+        }
+    }
+
+    fn position(&self, bary: Vector3) -> Vector3 {
+        interpolate(&self.positions, bary)
+    }
+
+    /// Returns the interpolated normal, or zero vector, if there is none.
+    fn normal(&self, bary: Vector3) -> Vector3 {
+        interpolate(&self.normals, bary)
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_default()
+    }
+
+    fn color(&self, bary: Vector3) -> Option<Vector3> {
+        self.colors.map(|colors| interpolate(&colors, bary))
+    }
+
+    fn uv(&self, bary: Vector3) -> Option<Vector2> {
+        self.uvs.map(|uvs| interpolate(&uvs, bary))
+    }
+
+    /// Returns the Lambertian light, that reaches the surface from `lights` and `ambient`.
+    ///
+    /// Used by materials, that can be optionally lit, together with [`tonemap`].
+    fn diffuse(&self, lights: &[Light], ambient: Vector3, bary: Vector3) -> Vector3 {
+        let normal = self.normal(bary);
+        let pos = self.position(bary);
+        lights
+            .iter()
+            .filter_map(|light| light.illuminate(pos))
+            .fold(ambient, |acc, (light_dir, light_col)| {
+                acc + light_col * libm::fmaxf(0.0, normal.dot(&light_dir))
+            })
+    }
+}
+
+/// Location of the viewer, recovered from a view-projection matrix.
+#[derive(Clone, Copy, Debug)]
+enum Eye {
+    /// Eye position of a perspective projection.
+    Point(Vector3),
+    /// Direction towards the viewer of an orthographic projection.
+    Direction(Vector3),
+}
+
+impl Eye {
+    fn from_proj(proj: Matrix4) -> Self {
+        // The eye is the only point that maps to zero W in clip space. Under orthographic
+        // projection there is no such point, and we get the forward direction instead.
+        let inv = proj.try_inverse().unwrap_or_default();
+        let eye = inv * na::vector![0.0, 0.0, 1.0, 0.0];
+
+        if libm::fabsf(eye.w) > f32::EPSILON * eye.xyz().norm() {
+            Self::Point(eye.xyz() / eye.w)
+        } else {
+            Self::Direction(-eye.xyz().try_normalize(f32::EPSILON).unwrap_or_default())
+        }
+    }
+
+    /// Returns the direction from `pos` towards the viewer.
+    fn view_dir(&self, pos: Vector3) -> Vector3 {
+        match self {
+            Self::Point(eye) => (eye - pos).try_normalize(f32::EPSILON).unwrap_or_default(),
+            Self::Direction(dir) => *dir,
+        }
+    }
+}
+
+/// Applies Reinhard tone mapping.
+fn tonemap(color: Vector3) -> Vector3 {
+    color.component_div(&(color + na::vector![1.0, 1.0, 1.0]))
+}
+
+/// Default ambient light of materials, that can be optionally lit.
+fn default_ambient() -> Vector3 {
+    na::vector![0.3, 0.3, 0.3]
+}
+
+/// Normal interpolation mode.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Shading {
+    /// Use a single normal for the whole primitive.
+    Flat,
+    /// Interpolate vertex normals across the primitive.
+    ///
+    /// Objects without vertex normals fall back to flat shading.
+    #[default]
+    Smooth,
+}
+
+impl AsMut<dyn Material> for dyn Material {
+    fn as_mut(&mut self) -> &mut Self {
+        self
+    }
+}
+
+impl AsMut<dyn Material + Send> for dyn Material + Send {
+    fn as_mut(&mut self) -> &mut Self {
+        self
+    }
+}
+
+/// Very simple form of material - shade everything gray
+///
+/// Objects with vertex colors are shaded with them instead.
+#[derive(Clone, Default)]
+pub struct Unlit {
+    colors: Vec<Option<[Vector3; 3]>>,
+}
+
+impl Material for Unlit {
+    fn new_frame(&mut self, _: &FrameContext) {
+        self.colors.clear();
+    }
+
+    fn primitive_shade(
+        &mut self,
+        mut pri: Primitive,
+        attrs: &VertexAttrs,
+        proj: na::Matrix4<f32>,
+        model: na::Matrix4<f32>,
+    ) -> (usize, Primitive) {
+        let idx = self.colors.len();
+        self.colors.push(attrs.colors);
+
+        pri.transform(&(proj * model));
+
+        (idx, pri)
+    }
+
+    fn fragment_shade(
+        &self,
+        primitive: usize,
+        _pos: Vector2,
+        _: f32,
+        bary: Vector3,
+    ) -> Option<Vector4> {
+        let color = self.colors[primitive]
+            .map(|colors| interpolate(&colors, bary))
+            .unwrap_or(na::vector![0.5, 0.5, 0.5]);
+        Some(na::vector![color.x, color.y, color.z, 0.5])
+    }
+}
+
+/// Simple diffuse lighting shader.
+///
+/// Surfaces are lit by the lights of the scene, on top of constant ambient light. Vertex normals
+/// are interpolated across primitives, unless `shading` is set to [`Shading::Flat`]. Vertex
+/// colors, if present, tint the lit surface.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Diffuse {
+    pub shading: Shading,
+    /// Light, that reaches the surface from all directions.
+    pub ambient: Vector3,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    lights: Vec<Light>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    surfaces: Vec<Surface>,
+}
+
+impl Default for Diffuse {
+    fn default() -> Self {
+        Self {
+            shading: Shading::default(),
+            ambient: na::vector![0.1, 0.13, 0.25] * 5.0,
+            lights: alloc::vec![],
+            surfaces: alloc::vec![],
+        }
+    }
+}
+
+impl Material for Diffuse {
+    fn new_frame(&mut self, ctx: &FrameContext) {
+        self.surfaces.clear();
+        self.lights.clear();
+        self.lights.extend_from_slice(ctx.lights);
+    }
+
+    fn primitive_shade(
+        &mut self,
+        mut pri: Primitive,
+        attrs: &VertexAttrs,
+        proj: na::Matrix4<f32>,
+        model: na::Matrix4<f32>,
+    ) -> (usize, Primitive) {
+        let idx = self.surfaces.len();
+        self.surfaces
+            .push(Surface::new(&mut pri, attrs, self.shading, proj, model));
+        (idx, pri)
+    }
+
+    fn fragment_shade(
+        &self,
+        triangle: usize,
+        _pos: Vector2,
+        _: f32,
+        bary: Vector3,
+    ) -> Option<Vector4> {
+        let surface = &self.surfaces[triangle];
+        let normal = surface.normal(bary);
+        let pos = surface.position(bary);
+
+        let color = self
+            .lights
+            .iter()
+            .filter_map(|light| light.illuminate(pos))
+            .fold(self.ambient, |acc, (light_dir, light_col)| {
+                let light_dot = normal.dot(&light_dir);
+                acc + light_col * libm::fmaxf(0.0, libm::fminf(light_dot, 1.0))
+            });
+        let color = match surface.color(bary) {
+            Some(albedo) => color.component_mul(&albedo),
+            None => color,
+        };
+
+        let color = tonemap(color);
+
+        Some(na::vector![color.x, color.y, color.z, 1.0])
+    }
+}
+
+/// Text-only screen-space rendering
+///
+/// Implies orthographic projection with clip bounds of:
+///
+/// - X 0-100.
+/// - Y 0-100.
+/// - Z 1-1000.
+#[derive(Clone)]
+pub struct UiText {
+    idx: usize,
+    proj: na::Matrix4<f32>,
+}
+
+impl Default for UiText {
+    fn default() -> Self {
+        let dir = na::vector![0.0, 1.0, 0.0];
+        let view = Matrix4::look_at_rh(
+            &Default::default(),
+            &dir.into(),
+            &na::vector![0.0, 0.0, 1.0],
+        );
+
+        Self {
+            idx: 0,
+            proj: crate::extra::ortho_proj(0.0, 100.0, 0.0, 100.0, 1.0, 1000.0).as_matrix() * view,
+        }
+    }
+}
+
+impl Material for UiText {
+    fn new_frame(&mut self, _: &FrameContext) {
+        self.idx = 0;
+    }
+
+    fn primitive_shade(
+        &mut self,
+        mut pri: Primitive,
+        _: &VertexAttrs,
+        _: na::Matrix4<f32>,
+        model: na::Matrix4<f32>,
+    ) -> (usize, Primitive) {
+        let idx = self.idx;
+        self.idx += 1;
+
+        let proj = self.proj;
+
+        pri.transform(&(proj * model));
+
+        (idx, pri)
+    }
+
+    fn fragment_shade(&self, _: usize, _pos: Vector2, _: f32, _: Vector3) -> Option<Vector4> {
+        Some(na::vector![1.0, 1.0, 1.0, 0.0])
+    }
+}
@@ -0,0 +1,322 @@
+use super::*;
+
+/// Space, in which procedural patterns are evaluated.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Space {
+    /// Pattern moves together with the object.
+    #[default]
+    Object,
+    /// Pattern stays in place, and objects move through it.
+    World,
+}
+
+/// Procedural color pattern.
+///
+/// Patterns blend between colors `a` and `b`, based on a 3D position. They are evaluated with
+/// [`Pattern::sample`], which allows other materials to use them as a source of color.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type"))]
+pub enum Pattern {
+    /// 3D checkerboard of cubes with side length of `scale`.
+    Checkerboard { a: Vector3, b: Vector3, scale: f32 },
+    /// Parallel stripes of given `width`, perpendicular to `direction`.
+    Stripes {
+        a: Vector3,
+        b: Vector3,
+        direction: Vector3,
+        width: f32,
+    },
+    /// Gradient, going from `a` at point `from` to `b` at point `to`.
+    LinearGradient {
+        a: Vector3,
+        b: Vector3,
+        from: Vector3,
+        to: Vector3,
+    },
+    /// Gradient, going from `a` at `center` to `b` at `radius` away from it.
+    RadialGradient {
+        a: Vector3,
+        b: Vector3,
+        center: Vector3,
+        radius: f32,
+    },
+    /// Fractal value noise with features of size `scale`.
+    ///
+    /// At most 16 `octaves` are summed.
+    ValueNoise {
+        a: Vector3,
+        b: Vector3,
+        scale: f32,
+        octaves: u32,
+    },
+    /// Fractal Perlin noise with features of size `scale`.
+    ///
+    /// At most 16 `octaves` are summed.
+    Perlin {
+        a: Vector3,
+        b: Vector3,
+        scale: f32,
+        octaves: u32,
+    },
+}
+
+impl Default for Pattern {
+    fn default() -> Self {
+        Self::Checkerboard {
+            a: na::vector![0.9, 0.9, 0.9],
+            b: na::vector![0.2, 0.2, 0.2],
+            scale: 0.25,
+        }
+    }
+}
+
+impl Pattern {
+    /// Returns the color of the pattern at given position.
+    pub fn sample(&self, pos: Vector3) -> Vector3 {
+        // Cell boundaries often line up with faces, nudge them off the surface.
+        const BIAS: f32 = 1e-3;
+
+        let (a, b, t) = match *self {
+            Self::Checkerboard { a, b, scale } => {
+                let cell = (pos / scale).map(|v| libm::floorf(v + BIAS) as i64);
+                let parity = cell.x.wrapping_add(cell.y).wrapping_add(cell.z) & 1;
+                (a, b, parity as f32)
+            }
+            Self::Stripes {
+                a,
+                b,
+                direction,
+                width,
+            } => {
+                let dir = direction.try_normalize(f32::EPSILON).unwrap_or_default();
+                let stripe = libm::floorf(pos.dot(&dir) / width + BIAS) as i64;
+                (a, b, (stripe & 1) as f32)
+            }
+            Self::LinearGradient { a, b, from, to } => {
+                let axis = to - from;
+                let t = (pos - from).dot(&axis) / axis.norm_squared();
+                (a, b, if t.is_finite() { t } else { 0.0 })
+            }
+            Self::RadialGradient {
+                a,
+                b,
+                center,
+                radius,
+            } => (a, b, (pos - center).norm() / radius),
+            Self::ValueNoise {
+                a,
+                b,
+                scale,
+                octaves,
+            } => (a, b, fbm(pos / scale, octaves, value_noise)),
+            Self::Perlin {
+                a,
+                b,
+                scale,
+                octaves,
+            } => (
+                a,
+                b,
+                fbm(pos / scale, octaves, |p| perlin_noise(p) * 0.5 + 0.5),
+            ),
+        };
+
+        a.lerp(&b, t.clamp(0.0, 1.0))
+    }
+}
+
+/// Maximum number of noise octaves, finer ones are below the resolution of any terminal.
+const MAX_OCTAVES: u32 = 16;
+
+/// Sums octaves of noise, returning a value in `[0; 1]` range.
+///
+/// The number of octaves is clamped to `[1; MAX_OCTAVES]` range.
+fn fbm(pos: Vector3, octaves: u32, noise: impl Fn(Vector3) -> f32) -> f32 {
+    let mut sum = 0.0;
+    let mut total = 0.0;
+    let mut amplitude = 1.0;
+    let mut pos = pos;
+
+    for _ in 0..octaves.clamp(1, MAX_OCTAVES) {
+        sum += noise(pos) * amplitude;
+        total += amplitude;
+        amplitude *= 0.5;
+        pos *= 2.0;
+    }
+
+    sum / total
+}
+
+/// Hashes a lattice point.
+pub(crate) fn hash(x: i32, y: i32, z: i32) -> u32 {
+    let mut h = (x as u32).wrapping_mul(0x8da6b343)
+        ^ (y as u32).wrapping_mul(0xd8163841)
+        ^ (z as u32).wrapping_mul(0xcb1ab31f);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x2c1b3c6d);
+    h ^= h >> 12;
+    h = h.wrapping_mul(0x297a2d39);
+    h ^ (h >> 15)
+}
+
+/// Quintic interpolation curve, which has zero first and second derivatives at the ends.
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+/// Splits a position into the lattice cell, and fade curve weights within it.
+fn lattice(pos: Vector3) -> ([i32; 3], Vector3, Vector3) {
+    let floor = pos.map(libm::floorf);
+    let frac = pos - floor;
+    (
+        [floor.x as i32, floor.y as i32, floor.z as i32],
+        frac,
+        frac.map(fade),
+    )
+}
+
+/// Trilinearly interpolates values at the corners of a lattice cell.
+fn trilinear(w: Vector3, corner: impl Fn(i32, i32, i32) -> f32) -> f32 {
+    let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+    let face = |z| {
+        lerp(
+            lerp(corner(0, 0, z), corner(1, 0, z), w.x),
+            lerp(corner(0, 1, z), corner(1, 1, z), w.x),
+            w.y,
+        )
+    };
+    lerp(face(0), face(1), w.z)
+}
+
+/// Value noise in `[0; 1]` range.
+fn value_noise(pos: Vector3) -> f32 {
+    let ([x, y, z], _, w) = lattice(pos);
+    trilinear(w, |dx, dy, dz| {
+        hash(x.wrapping_add(dx), y.wrapping_add(dy), z.wrapping_add(dz)) as f32 / u32::MAX as f32
+    })
+}
+
+/// Perlin gradient noise in `[-1; 1]` range.
+fn perlin_noise(pos: Vector3) -> f32 {
+    let ([x, y, z], frac, w) = lattice(pos);
+    trilinear(w, |dx, dy, dz| {
+        let d = frac - na::vector![dx as f32, dy as f32, dz as f32];
+        // Dot product with one of 12 gradients towards the edges of a cube.
+        match hash(x.wrapping_add(dx), y.wrapping_add(dy), z.wrapping_add(dz)) % 12 {
+            0 => d.x + d.y,
+            1 => -d.x + d.y,
+            2 => d.x - d.y,
+            3 => -d.x - d.y,
+            4 => d.x + d.z,
+            5 => -d.x + d.z,
+            6 => d.x - d.z,
+            7 => -d.x - d.z,
+            8 => d.y + d.z,
+            9 => -d.y + d.z,
+            10 => d.y - d.z,
+            _ => -d.y - d.z,
+        }
+    })
+}
+
+/// Material, colored by a procedural [`Pattern`].
+///
+/// The pattern is evaluated at object or world space position of the fragment, depending on
+/// `space`. Vertex colors, if present, tint the pattern. When `lit` is set, the surface is
+/// additionally shaded by the lights of the scene, similarly to [`Diffuse`].
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Procedural {
+    pub pattern: Pattern,
+    pub space: Space,
+    pub lit: bool,
+    /// Light, that reaches the surface from all directions, when `lit` is set.
+    pub ambient: Vector3,
+    pub shading: Shading,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    lights: Vec<Light>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    surfaces: Vec<(Surface, [Vector3; 3])>,
+}
+
+impl Default for Procedural {
+    fn default() -> Self {
+        Self {
+            pattern: Pattern::default(),
+            space: Space::default(),
+            lit: false,
+            ambient: default_ambient(),
+            shading: Shading::default(),
+            lights: alloc::vec![],
+            surfaces: alloc::vec![],
+        }
+    }
+}
+
+impl Procedural {
+    pub fn new(pattern: Pattern) -> Self {
+        Self {
+            pattern,
+            ..Default::default()
+        }
+    }
+}
+
+impl Material for Procedural {
+    fn new_frame(&mut self, ctx: &FrameContext) {
+        self.surfaces.clear();
+        self.lights.clear();
+        self.lights.extend_from_slice(ctx.lights);
+    }
+
+    fn primitive_shade(
+        &mut self,
+        mut pri: Primitive,
+        attrs: &VertexAttrs,
+        proj: Matrix4,
+        model: Matrix4,
+    ) -> (usize, Primitive) {
+        let local = match pri {
+            Primitive::Triangle(Triangle { a, b, c }) => [a, b, c],
+            Primitive::Line(Line { start, end }) => [start, end, end],
+            Primitive::Point(Point { pos, .. }) => [pos; 3],
+        }
+        .map(|v| v.xyz() / v.w);
+
+        let idx = self.surfaces.len();
+        let surface = Surface::new(&mut pri, attrs, self.shading, proj, model);
+        self.surfaces.push((surface, local));
+        (idx, pri)
+    }
+
+    fn fragment_shade(
+        &self,
+        primitive: usize,
+        _pos: Vector2,
+        _: f32,
+        bary: Vector3,
+    ) -> Option<Vector4> {
+        let (surface, local) = &self.surfaces[primitive];
+
+        let pattern_pos = match self.space {
+            Space::Object => interpolate(local, bary),
+            Space::World => surface.position(bary),
+        };
+
+        let mut color = self.pattern.sample(pattern_pos);
+
+        if let Some(tint) = surface.color(bary) {
+            color.component_mul_assign(&tint);
+        }
+
+        if self.lit {
+            let light = surface.diffuse(&self.lights, self.ambient, bary);
+            color = tonemap(color.component_mul(&light));
+        }
+
+        Some(na::vector![color.x, color.y, color.z, 1.0])
+    }
+}
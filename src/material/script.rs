@@ -0,0 +1,362 @@
+use super::*;
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::rc::Rc;
+use alloc::string::String;
+use core::cell::RefCell;
+use rhai::{CallFnOptions, Dynamic, Engine, EvalAltResult, Position, Scope, AST};
+
+/// Value of a script uniform.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
+pub enum Uniform {
+    Float(f32),
+    Vec2(Vector2),
+    Vec3(Vector3),
+    Vec4(Vector4),
+}
+
+impl From<Uniform> for Dynamic {
+    fn from(v: Uniform) -> Self {
+        match v {
+            Uniform::Float(v) => Dynamic::from(v),
+            Uniform::Vec2(v) => Dynamic::from(v),
+            Uniform::Vec3(v) => Dynamic::from(v),
+            Uniform::Vec4(v) => Dynamic::from(v),
+        }
+    }
+}
+
+/// Material, shaded by a Rhai script.
+///
+/// The script must define a `fragment()` function, which returns the color of a fragment as
+/// `vec4`, or `vec3` for opaque colors. Returning `()` discards the fragment. The following
+/// variables are in scope:
+///
+/// - `position` - world space position of the fragment (`vec3`).
+/// - `screen` - screen position of the fragment (`vec2`).
+/// - `depth` - depth of the fragment.
+/// - `normal` - world space normal, zero for lines and points (`vec3`).
+/// - `uv` - texture coordinates, zero if the object has none (`vec2`).
+/// - `color` - vertex color, white if the object has none (`vec3`).
+/// - `time` - time of the frame, in seconds.
+/// - `frame` - number of the frame.
+/// - `camera_pos` - world space position of the camera (`vec3`).
+/// - `camera_dir` - world space direction the camera is looking at (`vec3`).
+/// - `resolution` - width and height of the screen, in cells (`vec2`).
+/// - every entry of `uniforms`, by name.
+///
+/// Optionally, the script may define a `vertex(position, normal)` function, which is called for
+/// every vertex of a primitive with its world space position and normal, and returns the
+/// displaced position (`vec3`). The frame variables and the uniforms are in scope there as well.
+///
+/// Fragments, for which the script fails, are drawn magenta. This includes scripts, that exceed
+/// the limits on operations, call depth, or expression depth, which keep runaway scripts (such as
+/// infinite loops) from hanging the renderer.
+///
+/// Scripts are compiled when the material is created, and clones share the compiled form, as do
+/// materials created through the same [`ScriptCache`]. This is still far slower than native
+/// materials, and is meant for experimentation.
+pub struct ScriptMaterial {
+    source: Arc<str>,
+    engine: Rc<Engine>,
+    ast: Rc<AST>,
+    has_vertex: bool,
+    /// Values, that are available to the script as variables.
+    pub uniforms: BTreeMap<String, Uniform>,
+    pub shading: Shading,
+    scope: RefCell<Scope<'static>>,
+    surfaces: Vec<Surface>,
+}
+
+impl Clone for ScriptMaterial {
+    fn clone(&self) -> Self {
+        Self {
+            source: self.source.clone(),
+            engine: self.engine.clone(),
+            ast: self.ast.clone(),
+            has_vertex: self.has_vertex,
+            uniforms: self.uniforms.clone(),
+            shading: self.shading,
+            scope: RefCell::new(Scope::new()),
+            surfaces: self.surfaces.clone(),
+        }
+    }
+}
+
+/// Maximum number of operations in a single call of a script function.
+const MAX_OPERATIONS: u64 = 10_000;
+/// Maximum depth of nested function calls.
+const MAX_CALL_LEVELS: usize = 32;
+/// Maximum nesting depth of expressions.
+const MAX_EXPR_DEPTH: usize = 64;
+
+/// Compiled scripts, shared between script materials.
+///
+/// Materials created through the same cache share the script engine, and scripts with identical
+/// source are compiled only once.
+#[derive(Clone)]
+pub struct ScriptCache {
+    engine: Rc<Engine>,
+    scripts: BTreeMap<Arc<str>, Rc<AST>>,
+}
+
+impl Default for ScriptCache {
+    fn default() -> Self {
+        let mut engine = Engine::new();
+        engine
+            .set_max_operations(MAX_OPERATIONS)
+            .set_max_call_levels(MAX_CALL_LEVELS)
+            .set_max_expr_depths(MAX_EXPR_DEPTH, MAX_EXPR_DEPTH);
+        register_math(&mut engine);
+
+        Self {
+            engine: Rc::new(engine),
+            scripts: BTreeMap::new(),
+        }
+    }
+}
+
+impl ScriptCache {
+    /// Returns the compiled form of a script, compiling it, if it is not cached yet.
+    fn compile(&mut self, source: &Arc<str>) -> Result<Rc<AST>, Box<EvalAltResult>> {
+        if let Some(ast) = self.scripts.get(source) {
+            return Ok(ast.clone());
+        }
+
+        let ast = Rc::new(self.engine.compile(&**source)?);
+        self.scripts.insert(source.clone(), ast.clone());
+
+        Ok(ast)
+    }
+
+    /// Drops compiled scripts, that are no longer used by any material.
+    pub fn prune(&mut self) {
+        self.scripts.retain(|_, ast| Rc::strong_count(ast) > 1);
+    }
+}
+
+impl ScriptMaterial {
+    /// Compiles a script material.
+    ///
+    /// Fails, if the script does not compile, or does not define a `fragment()` function.
+    pub fn new(source: Arc<str>) -> Result<Self, Box<EvalAltResult>> {
+        Self::with_cache(source, &mut ScriptCache::default())
+    }
+
+    /// Creates a script material, reusing the compiled script from `cache`, if there is one.
+    ///
+    /// Fails, if the script does not compile, or does not define a `fragment()` function.
+    pub fn with_cache(
+        source: Arc<str>,
+        cache: &mut ScriptCache,
+    ) -> Result<Self, Box<EvalAltResult>> {
+        let ast = cache.compile(&source)?;
+
+        let has_fn = |name: &str, params: usize| {
+            ast.iter_functions()
+                .any(|f| f.name == name && f.params.len() == params)
+        };
+
+        if !has_fn("fragment", 0) {
+            return Err(
+                EvalAltResult::ErrorFunctionNotFound("fragment()".into(), Position::NONE).into(),
+            );
+        }
+
+        Ok(Self {
+            has_vertex: has_fn("vertex", 2),
+            source,
+            engine: cache.engine.clone(),
+            ast,
+            uniforms: Default::default(),
+            shading: Default::default(),
+            scope: RefCell::new(Scope::new()),
+            surfaces: alloc::vec![],
+        })
+    }
+
+    /// Returns the source of the script.
+    pub fn source(&self) -> &Arc<str> {
+        &self.source
+    }
+
+    fn call(
+        &self,
+        scope: &mut Scope<'static>,
+        name: &str,
+        args: impl rhai::FuncArgs,
+    ) -> Option<Dynamic> {
+        // Top level statements are not evaluated, so that only the functions run per call.
+        let options = CallFnOptions::new().eval_ast(false);
+        self.engine
+            .call_fn_with_options(options, scope, &self.ast, name, args)
+            .ok()
+    }
+}
+
+impl Material for ScriptMaterial {
+    fn new_frame(&mut self, ctx: &FrameContext) {
+        self.surfaces.clear();
+
+        let scope = self.scope.get_mut();
+        scope.clear();
+        scope.push("time", ctx.time);
+        scope.push("frame", ctx.frame as rhai::INT);
+        scope.push("camera_pos", ctx.camera_pos);
+        scope.push("camera_dir", ctx.camera_dir);
+        scope.push(
+            "resolution",
+            na::vector![ctx.viewport.0 as f32, ctx.viewport.1 as f32],
+        );
+
+        for (name, value) in &self.uniforms {
+            scope.push_dynamic(name.as_str(), (*value).into());
+        }
+
+        for name in ["position", "normal", "color"] {
+            scope.push(name, Vector3::default());
+        }
+        scope.push("screen", Vector2::default());
+        scope.push("uv", Vector2::default());
+        scope.push("depth", 0f32);
+    }
+
+    fn primitive_shade(
+        &mut self,
+        mut pri: Primitive,
+        attrs: &VertexAttrs,
+        proj: Matrix4,
+        model: Matrix4,
+    ) -> (usize, Primitive) {
+        let mut surface = Surface::new(&mut pri, attrs, self.shading, Matrix4::identity(), model);
+
+        if self.has_vertex {
+            let scope = &mut *self.scope.borrow_mut();
+
+            for (pos, normal) in surface.positions.iter_mut().zip(surface.normals) {
+                if let Some(new) = self
+                    .call(scope, "vertex", (*pos, normal))
+                    .and_then(|v| v.try_cast::<Vector3>())
+                {
+                    *pos = new;
+                }
+            }
+
+            let [a, b, c] = surface.positions.map(|p| p.push(1.0));
+
+            match &mut pri {
+                Primitive::Triangle(t) => *t = Triangle { a, b, c },
+                Primitive::Line(Line { start, end }) => {
+                    *start = a;
+                    *end = b;
+                }
+                Primitive::Point(Point { pos, .. }) => *pos = a,
+            }
+        }
+
+        pri.transform(&proj);
+
+        let idx = self.surfaces.len();
+        self.surfaces.push(surface);
+        (idx, pri)
+    }
+
+    fn fragment_shade(
+        &self,
+        primitive: usize,
+        pos: Vector2,
+        depth: f32,
+        bary: Vector3,
+    ) -> Option<Vector4> {
+        let surface = &self.surfaces[primitive];
+        let scope = &mut *self.scope.borrow_mut();
+
+        scope.set_value("position", surface.position(bary));
+        scope.set_value("screen", pos);
+        scope.set_value("depth", depth);
+        scope.set_value("normal", surface.normal(bary));
+        scope.set_value("uv", surface.uv(bary).unwrap_or_default());
+        scope.set_value(
+            "color",
+            surface.color(bary).unwrap_or(na::vector![1.0, 1.0, 1.0]),
+        );
+
+        let Some(out) = self.call(scope, "fragment", ()) else {
+            return Some(na::vector![1.0, 0.0, 1.0, 1.0]);
+        };
+
+        if out.is_unit() {
+            None
+        } else if let Some(color) = out.clone().try_cast::<Vector4>() {
+            Some(color)
+        } else if let Some(color) = out.try_cast::<Vector3>() {
+            Some(color.push(1.0))
+        } else {
+            Some(na::vector![1.0, 0.0, 1.0, 1.0])
+        }
+    }
+}
+
+/// Registers vector types, and math functions, used by scripts.
+pub(crate) fn register_math(engine: &mut Engine) {
+    engine
+        .register_fn("sin", f32::sin)
+        .register_fn("cos", f32::cos)
+        .register_fn("atan2", f32::atan2)
+        .register_fn("clamp", |v: f32, lo: f32, hi: f32| v.max(lo).min(hi))
+        .register_fn("mix", |a: f32, b: f32, t: f32| a + (b - a) * t)
+        .register_fn("smoothstep", |lo: f32, hi: f32, v: f32| {
+            let t = ((v - lo) / (hi - lo)).clamp(0.0, 1.0);
+            t * t * (3.0 - 2.0 * t)
+        })
+        .register_type::<Vector4>()
+        .register_fn("vec4", Vector4::new)
+        .register_get("x", |v: &mut Vector4| v.x)
+        .register_get("y", |v: &mut Vector4| v.y)
+        .register_get("z", |v: &mut Vector4| v.z)
+        .register_get("w", |v: &mut Vector4| v.w)
+        .register_set("x", |v: &mut Vector4, x: f32| v.x = x)
+        .register_set("y", |v: &mut Vector4, y: f32| v.y = y)
+        .register_set("z", |v: &mut Vector4, z: f32| v.z = z)
+        .register_set("w", |v: &mut Vector4, w: f32| v.w = w)
+        .register_type::<Vector3>()
+        .register_fn("vec3", Vector3::new)
+        .register_get("x", |v: &mut Vector3| v.x)
+        .register_get("y", |v: &mut Vector3| v.y)
+        .register_get("z", |v: &mut Vector3| v.z)
+        .register_set("x", |v: &mut Vector3, x: f32| v.x = x)
+        .register_set("y", |v: &mut Vector3, y: f32| v.y = y)
+        .register_set("z", |v: &mut Vector3, z: f32| v.z = z)
+        .register_fn("cross", |a: Vector3, b: Vector3| a.cross(&b))
+        .register_type::<Vector2>()
+        .register_fn("vec2", Vector2::new)
+        .register_get("x", |v: &mut Vector2| v.x)
+        .register_get("y", |v: &mut Vector2| v.y)
+        .register_set("x", |v: &mut Vector2, x: f32| v.x = x)
+        .register_set("y", |v: &mut Vector2, y: f32| v.y = y);
+
+    macro_rules! vector_ops {
+        ($($ty:ty),*) => {
+            $(
+                engine
+                    .register_fn("+", |a: $ty, b: $ty| a + b)
+                    .register_fn("-", |a: $ty, b: $ty| a - b)
+                    .register_fn("-", |a: $ty| -a)
+                    .register_fn("*", |a: $ty, b: $ty| a.component_mul(&b))
+                    .register_fn("*", |a: $ty, b: f32| a * b)
+                    .register_fn("*", |a: f32, b: $ty| b * a)
+                    .register_fn("/", |a: $ty, b: f32| a / b)
+                    .register_fn("dot", |a: $ty, b: $ty| a.dot(&b))
+                    .register_fn("length", |v: $ty| v.norm())
+                    .register_fn("normalize", |v: $ty| {
+                        v.try_normalize(f32::EPSILON).unwrap_or_default()
+                    })
+                    .register_fn("mix", |a: $ty, b: $ty, t: f32| a.lerp(&b, t));
+            )*
+        };
+    }
+
+    vector_ops!(Vector2, Vector3, Vector4);
+}
@@ -0,0 +1,89 @@
+use super::*;
+use crate::texture::Texture;
+
+/// Texture mapped material.
+///
+/// Samples `texture` with interpolated texture coordinates of the primitive, and multiplies the
+/// result with vertex colors, if present. Primitives without texture coordinates sample the
+/// bottom left corner of the texture. Texels with alpha below 0.5 are discarded.
+///
+/// When `lit` is set, the surface is additionally shaded by the lights of the scene, similarly to
+/// [`Diffuse`].
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Textured {
+    pub texture: Arc<Texture>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub lit: bool,
+    /// Light, that reaches the surface from all directions, when `lit` is set.
+    #[cfg_attr(feature = "serde", serde(default = "default_ambient"))]
+    pub ambient: Vector3,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub shading: Shading,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    lights: Vec<Light>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    surfaces: Vec<Surface>,
+}
+
+impl Textured {
+    pub fn new(texture: Arc<Texture>) -> Self {
+        Self {
+            texture,
+            lit: false,
+            ambient: default_ambient(),
+            shading: Shading::default(),
+            lights: alloc::vec![],
+            surfaces: alloc::vec![],
+        }
+    }
+}
+
+impl Material for Textured {
+    fn new_frame(&mut self, ctx: &FrameContext) {
+        self.surfaces.clear();
+        self.lights.clear();
+        self.lights.extend_from_slice(ctx.lights);
+    }
+
+    fn primitive_shade(
+        &mut self,
+        mut pri: Primitive,
+        attrs: &VertexAttrs,
+        proj: Matrix4,
+        model: Matrix4,
+    ) -> (usize, Primitive) {
+        let idx = self.surfaces.len();
+        self.surfaces
+            .push(Surface::new(&mut pri, attrs, self.shading, proj, model));
+        (idx, pri)
+    }
+
+    fn fragment_shade(
+        &self,
+        primitive: usize,
+        _pos: Vector2,
+        _: f32,
+        bary: Vector3,
+    ) -> Option<Vector4> {
+        let surface = &self.surfaces[primitive];
+        let texel = self.texture.sample(surface.uv(bary).unwrap_or_default());
+
+        if texel.w < 0.5 {
+            return None;
+        }
+
+        let mut color = texel.xyz();
+
+        if let Some(tint) = surface.color(bary) {
+            color.component_mul_assign(&tint);
+        }
+
+        if self.lit {
+            let light = surface.diffuse(&self.lights, self.ambient, bary);
+            color = tonemap(color.component_mul(&light));
+        }
+
+        Some(na::vector![color.x, color.y, color.z, 1.0])
+    }
+}
@@ -0,0 +1,158 @@
+use super::*;
+
+/// Rim lighting of [`Toon`] material.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rim {
+    pub color: Vector3,
+    /// Fraction of the projected radius covered by the rim, in `0-1` range.
+    pub width: f32,
+}
+
+/// Silhouette outline of [`Toon`] material.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Outline {
+    pub color: Vector3,
+    /// Fraction of the projected radius covered by the outline, in `0-1` range.
+    pub width: f32,
+}
+
+/// Cel shading material.
+///
+/// Light of every scene light is quantized into `bands` levels, producing flat regions of color
+/// instead of continuous gradients. Optionally, surfaces facing away from the camera are covered
+/// by a lit rim and a solid outline. Both are measured by the angle between the surface normal and
+/// the view direction, and their widths are relative to the radius of a sphere, thus they work
+/// best on smooth shaded, rounded objects.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Toon {
+    /// Albedo of the surface.
+    pub base_color: Vector3,
+    /// Light, that reaches the surface from all directions.
+    pub ambient: Vector3,
+    /// Number of lit levels (excluding the unlit one).
+    pub bands: u32,
+    pub rim: Option<Rim>,
+    pub outline: Option<Outline>,
+    pub shading: Shading,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    lights: Vec<Light>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    surfaces: Vec<Surface>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    eye: Option<(Matrix4, Eye)>,
+}
+
+impl Default for Toon {
+    fn default() -> Self {
+        Self {
+            base_color: na::vector![0.8, 0.8, 0.8],
+            ambient: na::vector![0.1, 0.13, 0.25] * 2.0,
+            bands: 3,
+            rim: None,
+            outline: Some(Outline {
+                color: Default::default(),
+                width: 0.1,
+            }),
+            shading: Shading::default(),
+            lights: alloc::vec![],
+            surfaces: alloc::vec![],
+            eye: None,
+        }
+    }
+}
+
+impl Material for Toon {
+    fn new_frame(&mut self, ctx: &FrameContext) {
+        self.surfaces.clear();
+        self.lights.clear();
+        self.lights.extend_from_slice(ctx.lights);
+    }
+
+    fn primitive_shade(
+        &mut self,
+        mut pri: Primitive,
+        attrs: &VertexAttrs,
+        proj: Matrix4,
+        model: Matrix4,
+    ) -> (usize, Primitive) {
+        if !matches!(self.eye, Some((p, _)) if p == proj) {
+            self.eye = Some((proj, Eye::from_proj(proj)));
+        }
+
+        let idx = self.surfaces.len();
+        self.surfaces
+            .push(Surface::new(&mut pri, attrs, self.shading, proj, model));
+        (idx, pri)
+    }
+
+    fn fragment_shade(
+        &self,
+        primitive: usize,
+        _pos: Vector2,
+        _: f32,
+        bary: Vector3,
+    ) -> Option<Vector4> {
+        let surface = &self.surfaces[primitive];
+        let normal = surface.normal(bary);
+        let pos = surface.position(bary);
+
+        // Distance from the center of a sphere, projected to the screen and scaled to the radius.
+        // Primitives without normals (lines and points) have no silhouette.
+        let edge = match self.eye {
+            Some((_, eye)) if normal != Vector3::zeros() => {
+                let facing = normal.dot(&eye.view_dir(pos)).clamp(0.0, 1.0);
+                libm::sqrtf(1.0 - facing * facing)
+            }
+            _ => 0.0,
+        };
+
+        if let Some(outline) = self.outline {
+            if edge > 1.0 - outline.width {
+                return Some(na::vector![
+                    outline.color.x,
+                    outline.color.y,
+                    outline.color.z,
+                    1.0
+                ]);
+            }
+        }
+
+        let bands = core::cmp::max(self.bands, 1) as f32;
+        let mut lit = false;
+
+        let light = self
+            .lights
+            .iter()
+            .filter_map(|light| light.illuminate(pos))
+            .fold(self.ambient, |acc, (light_dir, light_col)| {
+                let n_dot_l = normal.dot(&light_dir);
+                if n_dot_l > 0.0 {
+                    lit = true;
+                    acc + light_col * (libm::ceilf(n_dot_l * bands) / bands)
+                } else {
+                    acc
+                }
+            });
+
+        let albedo = match surface.color(bary) {
+            Some(color) => self.base_color.component_mul(&color),
+            None => self.base_color,
+        };
+
+        let mut color = albedo.component_mul(&light);
+
+        if let Some(rim) = self.rim {
+            if lit && edge > 1.0 - rim.width {
+                color += rim.color;
+            }
+        }
+
+        let color = tonemap(color);
+
+        Some(na::vector![color.x, color.y, color.z, 1.0])
+    }
+}
@@ -0,0 +1,303 @@
+//! Indexed triangle meshes.
+
+use crate::{
+    material::Material, Matrix4, Primitive, PrimitiveId, Triangle, Vector2, Vector3, Vector4,
+    VertexAttrs, VertexState,
+};
+use alloc::{collections::BTreeMap, vec, vec::Vec};
+
+/// Indexed triangle mesh.
+///
+/// Triangles are expected to be wound counter-clockwise, when viewed from the outside. Optional
+/// vertex normals point outwards, and are interpolated across triangles by materials that support
+/// smooth shading (such as [`Diffuse`](crate::material::Diffuse)).
+///
+/// Triangles, that index past the end of `vertices`, or of any of the present attributes, are
+/// skipped when rendering.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Mesh {
+    /// Model space vertex positions.
+    pub vertices: Vec<Vector3>,
+    /// Triangle vertex indices.
+    pub indices: Vec<[usize; 3]>,
+    /// Per-vertex normals.
+    ///
+    /// When present, this must have the same length as `vertices`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub normals: Option<Vec<Vector3>>,
+    /// Per-vertex colors.
+    ///
+    /// When present, this must have the same length as `vertices`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub colors: Option<Vec<Vector3>>,
+    /// Per-vertex texture coordinates.
+    ///
+    /// When present, this must have the same length as `vertices`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub uvs: Option<Vec<Vector2>>,
+    /// Per-vertex scalar values.
+    ///
+    /// When present, this must have the same length as `vertices`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub scalars: Option<Vec<f32>>,
+}
+
+impl Mesh {
+    pub fn new(vertices: Vec<Vector3>, indices: Vec<[usize; 3]>) -> Self {
+        Self {
+            vertices,
+            indices,
+            normals: None,
+            colors: None,
+            uvs: None,
+            scalars: None,
+        }
+    }
+
+    /// Creates a UV sphere with diameter of 1.
+    ///
+    /// `segments` is the number of subdivisions around the Z axis, while `rings` is the number of
+    /// subdivisions from pole to pole. Vertex normals are filled in analytically, and texture
+    /// coordinates are mapped equirectangularly, with V going from the bottom pole to the top one.
+    pub fn uv_sphere(segments: usize, rings: usize) -> Self {
+        let segments = core::cmp::max(segments, 3);
+        let rings = core::cmp::max(rings, 2);
+
+        let mut vertices = Vec::with_capacity((rings + 1) * (segments + 1));
+        let mut normals = Vec::with_capacity(vertices.capacity());
+        let mut uvs = Vec::with_capacity(vertices.capacity());
+
+        for i in 0..=rings {
+            let (sin_theta, cos_theta) = if i == rings {
+                // Keep the bottom pole exact so that all of its vertices coincide.
+                (0.0, -1.0)
+            } else {
+                libm::sincosf(core::f32::consts::PI * i as f32 / rings as f32)
+            };
+
+            for j in 0..=segments {
+                // The seam reuses the angle of the first segment to have matching positions.
+                let phi = 2.0 * core::f32::consts::PI * (j % segments) as f32 / segments as f32;
+                let (sin_phi, cos_phi) = libm::sincosf(phi);
+                let n = Vector3::new(sin_theta * cos_phi, sin_theta * sin_phi, cos_theta);
+                vertices.push(n * 0.5);
+                normals.push(n);
+                uvs.push(Vector2::new(
+                    j as f32 / segments as f32,
+                    1.0 - i as f32 / rings as f32,
+                ));
+            }
+        }
+
+        let mut indices = vec![];
+
+        for i in 0..rings {
+            for j in 0..segments {
+                let v0 = i * (segments + 1) + j;
+                let v1 = v0 + 1;
+                let v2 = v0 + segments + 1;
+                let v3 = v2 + 1;
+
+                // Skip the degenerate triangles at the poles
+                if i != rings - 1 {
+                    indices.push([v0, v2, v3]);
+                }
+                if i != 0 {
+                    indices.push([v0, v3, v1]);
+                }
+            }
+        }
+
+        Self {
+            vertices,
+            indices,
+            normals: Some(normals),
+            colors: None,
+            uvs: Some(uvs),
+            scalars: None,
+        }
+    }
+
+    /// Computes the normal of given triangle, scaled by twice its area.
+    fn face_normal(&self, [a, b, c]: [usize; 3]) -> Vector3 {
+        let [a, b, c] = [a, b, c].map(|i| self.vertices[i]);
+        (b - a).cross(&(c - a))
+    }
+
+    /// Generates smooth vertex normals.
+    ///
+    /// Normals of adjacent triangles are averaged (weighted by area), but only if the angle
+    /// between the triangles is below `angle_threshold` (in radians). Edges above the threshold
+    /// are kept sharp, which requires splitting vertices, therefore, this function may add new
+    /// vertices and rewrite the indices.
+    ///
+    /// Vertices with identical positions are treated as shared, even if their indices differ.
+    /// Triangles with indices out of range of `vertices` are removed.
+    pub fn generate_normals(&mut self, angle_threshold: f32) {
+        let cos_threshold = libm::cosf(angle_threshold);
+
+        let len = self.vertices.len();
+        self.indices.retain(|tri| tri.iter().all(|&i| i < len));
+
+        let face_normals: Vec<Vector3> = self
+            .indices
+            .iter()
+            .map(|&tri| self.face_normal(tri))
+            .collect();
+
+        // Group vertices by position, so that seams get smoothed out.
+        let mut welded = BTreeMap::new();
+        let position_ids: Vec<usize> = self
+            .vertices
+            .iter()
+            .map(|v| {
+                let len = welded.len();
+                // Adding zero turns negative zeroes into positive ones.
+                let key = [v.x, v.y, v.z].map(|c| (c + 0.0).to_bits());
+                *welded.entry(key).or_insert(len)
+            })
+            .collect();
+
+        let mut adjacent = vec![vec![]; welded.len()];
+
+        for (f, tri) in self.indices.iter().enumerate() {
+            for &v in tri {
+                adjacent[position_ids[v]].push(f);
+            }
+        }
+
+        let mut vertices = vec![];
+        let mut normals: Vec<Vector3> = vec![];
+        // New vertices each of the old ones was split into, used for deduplication.
+        let mut splits: Vec<Vec<usize>> = vec![vec![]; self.vertices.len()];
+
+        for (f, tri) in self.indices.iter_mut().enumerate() {
+            let face_dir = face_normals[f].try_normalize(f32::EPSILON);
+
+            for v in tri.iter_mut() {
+                let normal = adjacent[position_ids[*v]]
+                    .iter()
+                    .map(|&g| face_normals[g])
+                    .filter(|n| {
+                        match (face_dir, n.try_normalize(f32::EPSILON)) {
+                            (Some(a), Some(b)) => a.dot(&b) >= cos_threshold,
+                            // Degenerate triangles do not contribute any direction.
+                            _ => false,
+                        }
+                    })
+                    .fold(Vector3::default(), |acc, n| acc + n)
+                    .try_normalize(f32::EPSILON)
+                    .or(face_dir)
+                    .unwrap_or_default();
+
+                let existing = splits[*v]
+                    .iter()
+                    .copied()
+                    .find(|&i| normals[i].dot(&normal) >= 1.0 - 1e-5);
+
+                *v = if let Some(i) = existing {
+                    i
+                } else {
+                    let i = vertices.len();
+                    vertices.push(self.vertices[*v]);
+                    normals.push(normal);
+                    splits[*v].push(i);
+                    i
+                };
+            }
+        }
+
+        self.colors = self
+            .colors
+            .take()
+            .map(|colors| remap(&colors, &splits, vertices.len()));
+        self.uvs = self
+            .uvs
+            .take()
+            .map(|uvs| remap(&uvs, &splits, vertices.len()));
+        self.scalars = self
+            .scalars
+            .take()
+            .map(|scalars| remap(&scalars, &splits, vertices.len()));
+        self.vertices = vertices;
+        self.normals = Some(normals);
+    }
+
+    /// Returns the number of vertices, that have all of the present attributes.
+    fn complete_vertices(&self) -> usize {
+        [
+            self.normals.as_ref().map(Vec::len),
+            self.colors.as_ref().map(Vec::len),
+            self.uvs.as_ref().map(Vec::len),
+            self.scalars.as_ref().map(Vec::len),
+        ]
+        .into_iter()
+        .flatten()
+        .fold(self.vertices.len(), core::cmp::min)
+    }
+
+    /// Returns the triangles of the mesh, with their vertex attributes.
+    ///
+    /// Triangles with indices out of range are skipped.
+    pub(crate) fn triangles(&self) -> impl Iterator<Item = ([Vector4; 3], VertexAttrs)> + '_ {
+        let len = self.complete_vertices();
+
+        self.indices
+            .iter()
+            .filter(move |tri| tri.iter().all(|&i| i < len))
+            .map(|tri| {
+                let verts = tri.map(|i| {
+                    let v = self.vertices[i];
+                    Vector4::new(v.x, v.y, v.z, 1.0)
+                });
+                let attrs = VertexAttrs {
+                    normals: self.normals.as_ref().map(|n| tri.map(|i| n[i])),
+                    colors: self.colors.as_ref().map(|c| tri.map(|i| c[i])),
+                    uvs: self.uvs.as_ref().map(|u| tri.map(|i| u[i])),
+                    scalars: self.scalars.as_ref().map(|s| tri.map(|i| s[i])),
+                };
+                (verts, attrs)
+            })
+    }
+
+    pub(crate) fn gen(
+        &self,
+        proj: Matrix4,
+        model: Matrix4,
+        state: &mut VertexState,
+        material: &mut (impl Material + ?Sized),
+        obj_idx: usize,
+        mat_idx: usize,
+    ) {
+        for ([a, b, c], attrs) in self.triangles() {
+            let (pri_idx, primitive) = material.primitive_shade(
+                Primitive::Triangle(Triangle { a, b, c }),
+                &attrs,
+                proj,
+                model,
+            );
+            state.clip_and_push_primitive(
+                primitive,
+                PrimitiveId {
+                    mat_idx,
+                    obj_idx,
+                    pri_idx,
+                },
+            );
+        }
+    }
+}
+
+/// Copies per-vertex values over to split vertices.
+fn remap<T: Copy + Default>(values: &[T], splits: &[Vec<usize>], len: usize) -> Vec<T> {
+    let mut out = vec![T::default(); len];
+
+    for (value, split) in values.iter().zip(splits) {
+        for &i in split {
+            out[i] = *value;
+        }
+    }
+
+    out
+}
@@ -0,0 +1,708 @@
+//! Point clouds.
+
+use crate::{
+    material::Material, Matrix4, Point, Primitive, PrimitiveId, Vector3, Vector4, VertexAttrs,
+    VertexState,
+};
+use alloc::vec::Vec;
+
+/// Depth based brightness attenuation.
+///
+/// Points are dimmed linearly from full brightness at the near end of the range, to
+/// `min_brightness` at the far end. With character based outputs, dimmer points are drawn with
+/// less dense characters, which makes depth readable even without colors.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DepthAttenuation {
+    /// View depth range (near, far) to attenuate over.
+    ///
+    /// If `None`, the range of currently visible points is used.
+    pub range: Option<(f32, f32)>,
+    /// Brightness at the far end of the range.
+    pub min_brightness: f32,
+}
+
+impl Default for DepthAttenuation {
+    fn default() -> Self {
+        Self {
+            range: None,
+            min_brightness: 0.2,
+        }
+    }
+}
+
+/// Large set of points.
+///
+/// Point data is stored in flat buffers, rather than as separate objects. Each point is passed to
+/// the material as a [`Point`] primitive, with its color (if any) passed in
+/// [`VertexAttrs::colors`], and its intensity (if any) in [`VertexAttrs::scalars`]. Points past
+/// the end of a shorter attribute buffer are not drawn.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PointCloud {
+    /// Model space point positions.
+    pub positions: Vec<Vector3>,
+    /// Per-point colors.
+    ///
+    /// When present, this must have the same length as `positions`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub colors: Option<Vec<[u8; 3]>>,
+    /// Per-point intensities in the `0-1` range.
+    ///
+    /// These scale point colors. When present, this must have the same length as `positions`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub intensities: Option<Vec<f32>>,
+    /// Size of each point, in screen cells.
+    pub point_size: f32,
+    /// Optional depth based attenuation.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub depth_attenuation: Option<DepthAttenuation>,
+}
+
+impl Default for PointCloud {
+    fn default() -> Self {
+        Self {
+            positions: Vec::new(),
+            colors: None,
+            intensities: None,
+            point_size: 1.0,
+            depth_attenuation: None,
+        }
+    }
+}
+
+impl PointCloud {
+    pub fn new(positions: Vec<Vector3>) -> Self {
+        Self {
+            positions,
+            ..Default::default()
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Returns the number of points, that have all of the present attributes.
+    fn complete_points(&self) -> usize {
+        [
+            self.colors.as_ref().map(Vec::len),
+            self.intensities.as_ref().map(Vec::len),
+        ]
+        .into_iter()
+        .flatten()
+        .fold(self.positions.len(), core::cmp::min)
+    }
+
+    /// Returns the base color of the point, if it has any color data.
+    fn color(&self, i: usize) -> Option<Vector3> {
+        let color = self
+            .colors
+            .as_ref()
+            .map(|c| Vector3::from(c[i].map(|v| v as f32 / 255.0)));
+        match (color, self.intensities.as_ref().map(|v| v[i])) {
+            (Some(color), Some(intensity)) => Some(color * intensity),
+            (None, Some(intensity)) => Some(Vector3::repeat(intensity)),
+            (color, None) => color,
+        }
+    }
+
+    pub(crate) fn gen(
+        &self,
+        proj: Matrix4,
+        model: Matrix4,
+        state: &mut VertexState,
+        material: &mut (impl Material + ?Sized),
+        obj_idx: usize,
+        mat_idx: usize,
+    ) {
+        // Perspective projections have the view depth stored in `w`, while orthographic ones
+        // keep it linear in `z`.
+        let perspective = proj.row(3).transpose() != Vector4::new(0.0, 0.0, 0.0, 1.0);
+        let view_depth = |p: &Vector3| {
+            let clip = proj * model * Vector4::new(p.x, p.y, p.z, 1.0);
+            if perspective {
+                clip.w
+            } else {
+                clip.z
+            }
+        };
+
+        let attenuation = self.depth_attenuation.map(|att| {
+            let (near, far) = att.range.unwrap_or_else(|| {
+                self.positions
+                    .iter()
+                    .map(view_depth)
+                    .filter(|d| *d >= 0.0)
+                    .fold((f32::MAX, f32::MIN), |(min, max), d| {
+                        (min.min(d), max.max(d))
+                    })
+            });
+            (near, far, att.min_brightness)
+        });
+
+        let len = self.complete_points();
+
+        for (i, p) in self.positions[..len].iter().enumerate() {
+            let mut color = self.color(i);
+
+            if let Some((near, far, min_brightness)) = attenuation {
+                let t = if far > near {
+                    ((view_depth(p) - near) / (far - near)).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                let brightness = 1.0 + (min_brightness - 1.0) * t;
+                color = Some(color.unwrap_or(Vector3::repeat(1.0)) * brightness);
+            }
+
+            let point = Point {
+                pos: Vector4::new(p.x, p.y, p.z, 1.0),
+                size: self.point_size,
+            };
+            let attrs = VertexAttrs {
+                colors: color.map(|c| [c; 3]),
+                scalars: self.intensities.as_ref().map(|v| [v[i]; 3]),
+                ..Default::default()
+            };
+
+            let (pri_idx, primitive) =
+                material.primitive_shade(Primitive::Point(point), &attrs, proj, model);
+            state.clip_and_push_primitive(
+                primitive,
+                PrimitiveId {
+                    mat_idx,
+                    obj_idx,
+                    pri_idx,
+                },
+            );
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+mod io {
+    use super::*;
+    use std::io::{BufRead, Error, ErrorKind, Result};
+
+    fn invalid(msg: impl Into<alloc::string::String>) -> Error {
+        Error::new(ErrorKind::InvalidData, msg.into())
+    }
+
+    fn parse<T: core::str::FromStr>(v: &str) -> Result<T> {
+        v.parse()
+            .map_err(|_| invalid(alloc::format!("invalid number: {v}")))
+    }
+
+    /// Scalar property types of PLY files.
+    #[derive(Clone, Copy)]
+    enum PlyType {
+        I8,
+        U8,
+        I16,
+        U16,
+        I32,
+        U32,
+        F32,
+        F64,
+    }
+
+    impl PlyType {
+        fn parse(name: &str) -> Result<Self> {
+            Ok(match name {
+                "char" | "int8" => Self::I8,
+                "uchar" | "uint8" => Self::U8,
+                "short" | "int16" => Self::I16,
+                "ushort" | "uint16" => Self::U16,
+                "int" | "int32" => Self::I32,
+                "uint" | "uint32" => Self::U32,
+                "float" | "float32" => Self::F32,
+                "double" | "float64" => Self::F64,
+                _ => return Err(invalid(alloc::format!("unknown PLY type: {name}"))),
+            })
+        }
+
+        fn size(self) -> usize {
+            match self {
+                Self::I8 | Self::U8 => 1,
+                Self::I16 | Self::U16 => 2,
+                Self::I32 | Self::U32 | Self::F32 => 4,
+                Self::F64 => 8,
+            }
+        }
+
+        fn decode(self, b: &[u8], big_endian: bool) -> f64 {
+            macro_rules! num {
+                ($ty:ty) => {{
+                    let bytes = b.try_into().unwrap();
+                    (if big_endian {
+                        <$ty>::from_be_bytes(bytes)
+                    } else {
+                        <$ty>::from_le_bytes(bytes)
+                    }) as f64
+                }};
+            }
+
+            match self {
+                Self::I8 => num!(i8),
+                Self::U8 => num!(u8),
+                Self::I16 => num!(i16),
+                Self::U16 => num!(u16),
+                Self::I32 => num!(i32),
+                Self::U32 => num!(u32),
+                Self::F32 => num!(f32),
+                Self::F64 => num!(f64),
+            }
+        }
+
+        fn is_float(self) -> bool {
+            matches!(self, Self::F32 | Self::F64)
+        }
+    }
+
+    #[derive(Clone, Copy, PartialEq)]
+    enum PlyFormat {
+        Ascii,
+        LittleEndian,
+        BigEndian,
+    }
+
+    /// Accumulates point data while reading files.
+    #[derive(Default)]
+    struct Builder {
+        cloud: PointCloud,
+        colors: Vec<[u8; 3]>,
+        intensities: Vec<f32>,
+    }
+
+    impl Builder {
+        fn finish(mut self) -> PointCloud {
+            let len = self.cloud.positions.len();
+            if !self.colors.is_empty() && self.colors.len() == len {
+                self.cloud.colors = Some(self.colors);
+            }
+            if !self.intensities.is_empty() && self.intensities.len() == len {
+                self.cloud.intensities = Some(self.intensities);
+            }
+            self.cloud
+        }
+    }
+
+    fn color_channel(v: f64, is_float: bool) -> u8 {
+        if is_float {
+            (v * 255.0).clamp(0.0, 255.0) as u8
+        } else {
+            v.clamp(0.0, 255.0) as u8
+        }
+    }
+
+    impl PointCloud {
+        /// Reads a point cloud from an XYZ text file.
+        ///
+        /// Each line holds whitespace (or comma) separated values of a point:
+        ///
+        /// - `x y z`
+        /// - `x y z intensity`
+        /// - `x y z r g b`
+        /// - `x y z intensity r g b`
+        ///
+        /// Colors are expected in the `0-255` range, while intensities in `0-1`. Empty lines, and
+        /// lines starting with `#` or `//` are ignored.
+        pub fn from_xyz(reader: impl BufRead) -> Result<Self> {
+            let mut builder = Builder::default();
+
+            for line in reader.lines() {
+                let line = line?;
+                let line = line.trim();
+
+                if line.is_empty() || line.starts_with('#') || line.starts_with("//") {
+                    continue;
+                }
+
+                let values = line
+                    .split(|c: char| c.is_whitespace() || c == ',')
+                    .filter(|v| !v.is_empty())
+                    .map(parse::<f32>)
+                    .collect::<Result<Vec<_>>>()?;
+
+                if values.len() < 3 {
+                    return Err(invalid("XYZ line has fewer than 3 values"));
+                }
+
+                let (pos, rest) = values.split_at(3);
+
+                builder
+                    .cloud
+                    .positions
+                    .push(Vector3::new(pos[0], pos[1], pos[2]));
+
+                let rgb = |v: &[f32]| [0, 1, 2].map(|i| color_channel(v[i] as f64, false));
+
+                match rest {
+                    [] => {}
+                    [i] => builder.intensities.push(*i),
+                    [_, _, _] => builder.colors.push(rgb(rest)),
+                    [i, c @ ..] if c.len() == 3 => {
+                        builder.intensities.push(*i);
+                        builder.colors.push(rgb(c));
+                    }
+                    _ => return Err(invalid("unsupported number of XYZ columns")),
+                }
+            }
+
+            Ok(builder.finish())
+        }
+
+        /// Reads a point cloud from a PLY file.
+        ///
+        /// ASCII, and both binary formats are supported. Positions are read from `x`, `y`, `z`
+        /// properties of the `vertex` element, colors from `red`, `green`, `blue`, and
+        /// intensities from `intensity` (or `scalar_intensity`). Elements after `vertex` (such as
+        /// faces) are ignored, while elements before it must not contain list properties in
+        /// binary files.
+        pub fn from_ply(mut reader: impl BufRead) -> Result<Self> {
+            let mut line = alloc::string::String::new();
+
+            let mut read_line = |reader: &mut dyn BufRead| -> Result<alloc::string::String> {
+                line.clear();
+                if reader.read_line(&mut line)? == 0 {
+                    return Err(Error::new(ErrorKind::UnexpectedEof, "PLY header truncated"));
+                }
+                Ok(line.trim().into())
+            };
+
+            if read_line(&mut reader)? != "ply" {
+                return Err(invalid("missing PLY magic"));
+            }
+
+            let mut format = None;
+            // (name, count, properties (name, type, is list))
+            let mut elements: Vec<(alloc::string::String, usize, Vec<_>)> = Vec::new();
+
+            loop {
+                let line = read_line(&mut reader)?;
+                let mut words = line.split_whitespace();
+
+                match words.next() {
+                    Some("format") => {
+                        format = Some(match words.next() {
+                            Some("ascii") => PlyFormat::Ascii,
+                            Some("binary_little_endian") => PlyFormat::LittleEndian,
+                            Some("binary_big_endian") => PlyFormat::BigEndian,
+                            _ => return Err(invalid("unknown PLY format")),
+                        });
+                    }
+                    Some("element") => {
+                        let name = words.next().ok_or_else(|| invalid("unnamed element"))?;
+                        let count = parse(words.next().unwrap_or_default())?;
+                        elements.push((name.into(), count, Vec::new()));
+                    }
+                    Some("property") => {
+                        let (_, _, props) = elements
+                            .last_mut()
+                            .ok_or_else(|| invalid("property outside element"))?;
+                        let ty = words.next().unwrap_or_default();
+                        if ty == "list" {
+                            let count_ty = PlyType::parse(words.next().unwrap_or_default())?;
+                            let item_ty = PlyType::parse(words.next().unwrap_or_default())?;
+                            let name: alloc::string::String =
+                                words.next().unwrap_or_default().into();
+                            props.push((name, item_ty, Some(count_ty)));
+                        } else {
+                            let name = words.next().unwrap_or_default().into();
+                            props.push((name, PlyType::parse(ty)?, None));
+                        }
+                    }
+                    Some("end_header") => break,
+                    _ => {}
+                }
+            }
+
+            let format = format.ok_or_else(|| invalid("missing PLY format"))?;
+
+            let mut builder = Builder::default();
+            let mut ascii_lines = Vec::new();
+
+            for (name, count, props) in &elements {
+                let is_vertex = name == "vertex";
+
+                if format == PlyFormat::Ascii {
+                    for _ in 0..*count {
+                        let line = read_line(&mut reader)?;
+                        if !is_vertex {
+                            continue;
+                        }
+                        ascii_lines.clear();
+                        ascii_lines.extend(
+                            line.split_whitespace()
+                                .map(parse::<f64>)
+                                .collect::<Result<Vec<_>>>()?,
+                        );
+                        if ascii_lines.len() < props.len() {
+                            return Err(invalid("PLY vertex has too few values"));
+                        }
+                        push_vertex(&mut builder, props, |i| ascii_lines[i]);
+                    }
+                } else {
+                    if props.iter().any(|(_, _, list)| list.is_some()) {
+                        if is_vertex {
+                            return Err(invalid("list properties in PLY vertices"));
+                        }
+                        // Reading stops right after the vertices, thus this element precedes them,
+                        // and we can not skip over variable sized elements.
+                        return Err(invalid("cannot skip list elements before PLY vertices"));
+                    }
+
+                    let big_endian = format == PlyFormat::BigEndian;
+                    let stride: usize = props.iter().map(|(_, ty, _)| ty.size()).sum();
+                    let offsets: Vec<usize> = props
+                        .iter()
+                        .scan(0, |off, (_, ty, _)| {
+                            let ret = *off;
+                            *off += ty.size();
+                            Some(ret)
+                        })
+                        .collect();
+                    let mut buf = alloc::vec![0; stride];
+
+                    for _ in 0..*count {
+                        reader.read_exact(&mut buf)?;
+                        if is_vertex {
+                            push_vertex(&mut builder, props, |i| {
+                                let ty = props[i].1;
+                                ty.decode(&buf[offsets[i]..offsets[i] + ty.size()], big_endian)
+                            });
+                        }
+                    }
+                }
+
+                if is_vertex {
+                    break;
+                }
+            }
+
+            Ok(builder.finish())
+        }
+
+        /// Reads a point cloud, choosing the format based on file contents.
+        ///
+        /// PLY files are detected by their magic, everything else is parsed as XYZ.
+        pub fn read(mut reader: impl BufRead) -> Result<Self> {
+            if reader.fill_buf()?.starts_with(b"ply") {
+                Self::from_ply(reader)
+            } else {
+                Self::from_xyz(reader)
+            }
+        }
+    }
+
+    fn push_vertex(
+        builder: &mut Builder,
+        props: &[(alloc::string::String, PlyType, Option<PlyType>)],
+        value: impl Fn(usize) -> f64,
+    ) {
+        let find = |names: &[&str]| {
+            props
+                .iter()
+                .position(|(n, _, _)| names.contains(&n.as_str()))
+        };
+
+        let pos = [find(&["x"]), find(&["y"]), find(&["z"])].map(|i| i.map_or(0.0, &value) as f32);
+        builder.cloud.positions.push(Vector3::from(pos));
+
+        if let [Some(r), Some(g), Some(b)] = [
+            find(&["red", "r", "diffuse_red"]),
+            find(&["green", "g", "diffuse_green"]),
+            find(&["blue", "b", "diffuse_blue"]),
+        ] {
+            builder
+                .colors
+                .push([r, g, b].map(|i| color_channel(value(i), props[i].1.is_float())));
+        }
+
+        if let Some(i) = find(&["intensity", "scalar_intensity"]) {
+            let v = value(i);
+            // Integer intensities are normalized by the maximum value of their type.
+            let v = match props[i].1 {
+                PlyType::U8 => v / 255.0,
+                PlyType::U16 => v / 65535.0,
+                _ => v,
+            };
+            builder.intensities.push(v as f32);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use std::io::ErrorKind;
+
+    fn ply(header: &str, body: &[u8]) -> Vec<u8> {
+        let mut data = header.as_bytes().to_vec();
+        data.extend_from_slice(body);
+        data
+    }
+
+    #[test]
+    fn xyz_columns() {
+        let data = "# comment\n\n1 2 3 0.5 255 0 128\n// comment\n4,5,6,1,0,255,0\n";
+        let cloud = PointCloud::from_xyz(data.as_bytes()).unwrap();
+
+        assert_eq!(
+            cloud.positions,
+            [Vector3::new(1.0, 2.0, 3.0), Vector3::new(4.0, 5.0, 6.0)]
+        );
+        assert_eq!(cloud.intensities, Some(alloc::vec![0.5, 1.0]));
+        assert_eq!(cloud.colors, Some(alloc::vec![[255, 0, 128], [0, 255, 0]]));
+    }
+
+    #[test]
+    fn xyz_mixed_columns() {
+        // Attributes are only kept, when every point has them.
+        let cloud = PointCloud::from_xyz("1 2 3 0.5\n4 5 6\n".as_bytes()).unwrap();
+
+        assert_eq!(cloud.len(), 2);
+        assert_eq!(cloud.intensities, None);
+        assert_eq!(cloud.colors, None);
+    }
+
+    #[test]
+    fn xyz_invalid() {
+        for data in ["1 2\n", "1 2 3 4 5\n", "1 2 x\n", "1 2 3 4 5 6 7 8\n"] {
+            let err = PointCloud::from_xyz(data.as_bytes()).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::InvalidData, "{data:?}");
+        }
+    }
+
+    #[test]
+    fn ply_ascii() {
+        let data = ply(
+            "ply\nformat ascii 1.0\nelement vertex 2\nproperty float x\nproperty float y\n\
+             property float z\nproperty uchar red\nproperty uchar green\nproperty uchar blue\n\
+             property ushort intensity\nelement face 1\nproperty list uchar int vertex_indices\n\
+             end_header\n",
+            b"1 2 3 255 0 0 65535\n4 5 6 0 0 255 0\n3 0 1 1\n",
+        );
+        let cloud = PointCloud::from_ply(&data[..]).unwrap();
+
+        assert_eq!(
+            cloud.positions,
+            [Vector3::new(1.0, 2.0, 3.0), Vector3::new(4.0, 5.0, 6.0)]
+        );
+        assert_eq!(cloud.colors, Some(alloc::vec![[255, 0, 0], [0, 0, 255]]));
+        assert_eq!(cloud.intensities, Some(alloc::vec![1.0, 0.0]));
+    }
+
+    #[test]
+    fn ply_binary() {
+        let header = |endian| {
+            alloc::format!(
+                "ply\nformat binary_{endian}_endian 1.0\nelement camera 1\nproperty uchar id\n\
+                 element vertex 1\nproperty float x\nproperty short y\nproperty double z\n\
+                 property float red\nproperty float green\nproperty float blue\nend_header\n"
+            )
+        };
+
+        macro_rules! body {
+            ($to_bytes:ident) => {{
+                let mut body = alloc::vec![7];
+                body.extend(1.5f32.$to_bytes());
+                body.extend((-2i16).$to_bytes());
+                body.extend(3.0f64.$to_bytes());
+                for v in [1.0f32, 0.0, 2.0] {
+                    body.extend(v.$to_bytes());
+                }
+                body
+            }};
+        }
+
+        for (endian, body) in [("little", body!(to_le_bytes)), ("big", body!(to_be_bytes))] {
+            let cloud = PointCloud::read(&ply(&header(endian), &body)[..]).unwrap();
+            assert_eq!(cloud.positions, [Vector3::new(1.5, -2.0, 3.0)]);
+            // Float colors are in the `0-1` range.
+            assert_eq!(cloud.colors, Some(alloc::vec![[255, 0, 255]]));
+        }
+    }
+
+    #[test]
+    fn ply_truncated() {
+        let header = "ply\nformat ascii 1.0\nelement vertex 2\nproperty float x\n";
+        let err = PointCloud::from_ply(header.as_bytes()).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+
+        // The header promises more vertices, than there are.
+        let data = ply(
+            "ply\nformat binary_little_endian 1.0\nelement vertex 1000000000\nproperty float x\n\
+             end_header\n",
+            &[0; 6],
+        );
+        let err = PointCloud::from_ply(&data[..]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+
+        let data = ply(
+            "ply\nformat ascii 1.0\nelement vertex 1\nproperty float x\nproperty float y\n\
+             end_header\n",
+            b"1\n",
+        );
+        let err = PointCloud::from_ply(&data[..]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn ply_invalid_header() {
+        for header in [
+            "plx\n",
+            "ply\nelement vertex 1\nproperty float x\nend_header\n",
+            "ply\nformat binary_middle_endian 1.0\nend_header\n",
+            "ply\nformat ascii 1.0\nproperty float x\nend_header\n",
+            "ply\nformat ascii 1.0\nelement vertex -1\nend_header\n",
+            "ply\nformat ascii 1.0\nelement vertex 1\nproperty quad x\nend_header\n",
+        ] {
+            let err = PointCloud::from_ply(header.as_bytes()).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::InvalidData, "{header:?}");
+        }
+    }
+
+    #[test]
+    fn ply_binary_lists() {
+        // Variable sized elements can not be skipped before the vertices.
+        let data = ply(
+            "ply\nformat binary_little_endian 1.0\nelement face 1\n\
+             property list uchar int vertex_indices\nelement vertex 1\nproperty float x\n\
+             end_header\n",
+            &[1, 0, 0, 0, 0, 0, 0, 0, 0],
+        );
+        let err = PointCloud::from_ply(&data[..]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        let data = ply(
+            "ply\nformat binary_little_endian 1.0\nelement vertex 1\n\
+             property list uchar float x\nend_header\n",
+            &[1, 0, 0, 0, 0],
+        );
+        let err = PointCloud::from_ply(&data[..]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        // Lists after the vertices are never read.
+        let data = ply(
+            "ply\nformat binary_little_endian 1.0\nelement vertex 1\nproperty float x\n\
+             element face 1\nproperty list uchar int vertex_indices\nend_header\n",
+            &2.0f32.to_le_bytes(),
+        );
+        let cloud = PointCloud::from_ply(&data[..]).unwrap();
+        assert_eq!(cloud.positions, [Vector3::new(2.0, 0.0, 0.0)]);
+    }
+
+    #[test]
+    fn short_attributes() {
+        let mut cloud = PointCloud::new(alloc::vec![Vector3::default(); 4]);
+        cloud.colors = Some(alloc::vec![[0; 3]; 3]);
+        cloud.intensities = Some(alloc::vec![1.0; 2]);
+        assert_eq!(cloud.complete_points(), 2);
+    }
+}
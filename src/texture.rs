@@ -0,0 +1,591 @@
+//! Textures and image loading.
+
+use crate::{Vector2, Vector4};
+use alloc::vec::Vec;
+
+/// Texture sampling filter.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Filter {
+    /// Use the closest texel.
+    #[default]
+    Nearest,
+    /// Blend the 4 closest texels.
+    Bilinear,
+}
+
+/// Handling of texture coordinates outside the `0-1` range.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Wrap {
+    /// Tile the texture.
+    #[default]
+    Repeat,
+    /// Extend the edge texels.
+    Clamp,
+}
+
+impl Wrap {
+    fn apply(self, coord: isize, size: usize) -> usize {
+        match self {
+            Self::Repeat => coord.rem_euclid(size as isize) as usize,
+            Self::Clamp => coord.clamp(0, size as isize - 1) as usize,
+        }
+    }
+}
+
+/// RGBA image, sampled by materials.
+///
+/// Texels are stored in rows, top row first. Texture coordinates map `[0, 0]` to the bottom left
+/// corner of the image, and `[1, 1]` to the top right one.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "TextureDesc"))]
+pub struct Texture {
+    width: usize,
+    height: usize,
+    pixels: Vec<[u8; 4]>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub filter: Filter,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub wrap: Wrap,
+}
+
+/// Serialized form of [`Texture`], which is validated on deserialization.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct TextureDesc {
+    width: usize,
+    height: usize,
+    pixels: Vec<[u8; 4]>,
+    #[serde(default)]
+    filter: Filter,
+    #[serde(default)]
+    wrap: Wrap,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<TextureDesc> for Texture {
+    type Error = &'static str;
+
+    fn try_from(desc: TextureDesc) -> Result<Self, Self::Error> {
+        let mut texture = Self::from_pixels(desc.width, desc.height, desc.pixels)
+            .ok_or("texture size does not match its pixels")?;
+        texture.filter = desc.filter;
+        texture.wrap = desc.wrap;
+        Ok(texture)
+    }
+}
+
+impl Texture {
+    /// Creates a texture from tightly packed RGBA bytes.
+    ///
+    /// Returns `None`, if the image is empty, or the size of `data` does not match.
+    pub fn from_rgba8(width: usize, height: usize, data: &[u8]) -> Option<Self> {
+        if data.len() != width.checked_mul(height)?.checked_mul(4)? {
+            return None;
+        }
+
+        Self::from_pixels(
+            width,
+            height,
+            data.chunks_exact(4)
+                .map(|p| [p[0], p[1], p[2], p[3]])
+                .collect(),
+        )
+    }
+
+    /// Creates an opaque texture from tightly packed RGB bytes.
+    ///
+    /// Returns `None`, if the image is empty, or the size of `data` does not match.
+    pub fn from_rgb8(width: usize, height: usize, data: &[u8]) -> Option<Self> {
+        if data.len() != width.checked_mul(height)?.checked_mul(3)? {
+            return None;
+        }
+
+        Self::from_pixels(
+            width,
+            height,
+            data.chunks_exact(3)
+                .map(|p| [p[0], p[1], p[2], 255])
+                .collect(),
+        )
+    }
+
+    /// Creates a texture by evaluating `f(x, y)` for every texel.
+    ///
+    /// Returns `None`, if the image is empty.
+    pub fn from_fn(
+        width: usize,
+        height: usize,
+        mut f: impl FnMut(usize, usize) -> Vector4,
+    ) -> Option<Self> {
+        let pixels = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                f(x, y)
+                    .map(|v| (v.clamp(0.0, 1.0) * 255.0 + 0.5) as u8)
+                    .into()
+            })
+            .collect();
+        Self::from_pixels(width, height, pixels)
+    }
+
+    fn from_pixels(width: usize, height: usize, pixels: Vec<[u8; 4]>) -> Option<Self> {
+        if width == 0 || height == 0 || Some(pixels.len()) != width.checked_mul(height) {
+            return None;
+        }
+
+        Some(Self {
+            width,
+            height,
+            pixels,
+            filter: Filter::default(),
+            wrap: Wrap::default(),
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the texel at given position, counting from the top left corner.
+    ///
+    /// # Panics
+    ///
+    /// If the position is out of bounds.
+    pub fn pixel(&self, x: usize, y: usize) -> Vector4 {
+        assert!(x < self.width && y < self.height);
+        Vector4::from(self.pixels[y * self.width + x].map(|v| v as f32 / 255.0))
+    }
+
+    /// Samples the texture at given texture coordinates.
+    pub fn sample(&self, uv: Vector2) -> Vector4 {
+        let x = uv.x * self.width as f32;
+        let y = (1.0 - uv.y) * self.height as f32;
+
+        let texel = |x: f32, y: f32| {
+            self.pixel(
+                self.wrap.apply(x as isize, self.width),
+                self.wrap.apply(y as isize, self.height),
+            )
+        };
+
+        match self.filter {
+            Filter::Nearest => texel(libm::floorf(x), libm::floorf(y)),
+            Filter::Bilinear => {
+                let (x, y) = (x - 0.5, y - 0.5);
+                let (x0, y0) = (libm::floorf(x), libm::floorf(y));
+                let (fx, fy) = (x - x0, y - y0);
+
+                let top = texel(x0, y0) * (1.0 - fx) + texel(x0 + 1.0, y0) * fx;
+                let bottom = texel(x0, y0 + 1.0) * (1.0 - fx) + texel(x0 + 1.0, y0 + 1.0) * fx;
+
+                top * (1.0 - fy) + bottom * fy
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+mod io {
+    use super::*;
+    use std::io::{BufRead, Error, ErrorKind, Read, Result};
+
+    fn invalid(msg: impl Into<alloc::string::String>) -> Error {
+        Error::new(ErrorKind::InvalidData, msg.into())
+    }
+
+    fn invalid_size() -> Error {
+        invalid("invalid image size")
+    }
+
+    /// Largest width and height of loaded images.
+    ///
+    /// Headers are untrusted, and this keeps corrupted ones from allocating huge buffers.
+    const MAX_SIZE: usize = 16384;
+
+    /// Returns the number of pixels of an image, if its size is within limits.
+    fn image_len(width: usize, height: usize) -> Result<usize> {
+        if width > MAX_SIZE || height > MAX_SIZE {
+            return Err(invalid("image too large"));
+        }
+        width.checked_mul(height).ok_or_else(invalid_size)
+    }
+
+    impl Texture {
+        /// Reads a texture from a binary (`P6`) or ASCII (`P3`) PPM file.
+        pub fn from_ppm(mut reader: impl BufRead) -> Result<Self> {
+            let mut magic = [0; 2];
+            reader.read_exact(&mut magic)?;
+
+            let binary = match &magic {
+                b"P6" => true,
+                b"P3" => false,
+                _ => return Err(invalid("missing PPM magic")),
+            };
+
+            let width = ppm_number(&mut reader)?;
+            let height = ppm_number(&mut reader)?;
+            let max = ppm_number(&mut reader)?;
+
+            if max == 0 || max > 65535 {
+                return Err(invalid("invalid PPM maximum value"));
+            }
+
+            let len = image_len(width, height)?
+                .checked_mul(3)
+                .ok_or_else(invalid_size)?;
+
+            let data = if binary {
+                // Exactly one whitespace character separates the header from the data.
+                let bytes = if max > 255 { 2 } else { 1 };
+                let size = len.checked_mul(bytes).ok_or_else(invalid_size)?;
+                // Grow the buffer with the data, instead of trusting the header.
+                let mut raw = Vec::new();
+                (&mut reader).take(size as u64).read_to_end(&mut raw)?;
+                if raw.len() != size {
+                    return Err(Error::new(ErrorKind::UnexpectedEof, "PPM data truncated"));
+                }
+                raw.chunks_exact(bytes)
+                    .map(|v| v.iter().fold(0, |acc, &b| (acc << 8) | b as usize))
+                    .collect::<Vec<_>>()
+            } else {
+                (0..len)
+                    .map(|_| ppm_number(&mut reader))
+                    .collect::<Result<Vec<_>>>()?
+            };
+
+            let data: Vec<u8> = data
+                .into_iter()
+                .map(|v| ((v.min(max) * 255 + max / 2) / max) as u8)
+                .collect();
+
+            Self::from_rgb8(width, height, &data).ok_or_else(invalid_size)
+        }
+
+        /// Reads a texture from a QOI file.
+        pub fn from_qoi(mut reader: impl Read) -> Result<Self> {
+            let mut header = [0; 14];
+            reader.read_exact(&mut header)?;
+
+            if &header[..4] != b"qoif" {
+                return Err(invalid("missing QOI magic"));
+            }
+
+            let width = u32::from_be_bytes([header[4], header[5], header[6], header[7]]) as usize;
+            let height =
+                u32::from_be_bytes([header[8], header[9], header[10], header[11]]) as usize;
+            let len = image_len(width, height)?;
+
+            let mut data = Vec::new();
+            reader.read_to_end(&mut data)?;
+
+            // Every byte decodes to at most one run of 62 pixels.
+            let mut pixels = Vec::with_capacity(len.min(data.len().saturating_mul(62)));
+            let mut data = data.into_iter();
+            let mut next = || {
+                data.next()
+                    .ok_or_else(|| Error::new(ErrorKind::UnexpectedEof, "QOI data truncated"))
+            };
+
+            let mut index = [[0u8; 4]; 64];
+            let mut px = [0, 0, 0, 255u8];
+
+            while pixels.len() < len {
+                let tag = next()?;
+
+                match tag {
+                    0xfe => {
+                        px = [next()?, next()?, next()?, px[3]];
+                    }
+                    0xff => {
+                        px = [next()?, next()?, next()?, next()?];
+                    }
+                    _ => match tag >> 6 {
+                        0b00 => px = index[tag as usize & 0x3f],
+                        0b01 => {
+                            for (i, c) in px[..3].iter_mut().enumerate() {
+                                let diff = (tag >> (4 - 2 * i)) & 0b11;
+                                *c = c.wrapping_add(diff).wrapping_sub(2);
+                            }
+                        }
+                        0b10 => {
+                            let dg = (tag & 0x3f).wrapping_sub(32);
+                            let b = next()?;
+                            let dr = dg.wrapping_add(b >> 4).wrapping_sub(8);
+                            let db = dg.wrapping_add(b & 0xf).wrapping_sub(8);
+                            px[0] = px[0].wrapping_add(dr);
+                            px[1] = px[1].wrapping_add(dg);
+                            px[2] = px[2].wrapping_add(db);
+                        }
+                        _ => {
+                            let run = (tag & 0x3f) as usize + 1;
+                            for _ in 0..run.min(len - pixels.len()) {
+                                pixels.push(px);
+                            }
+                            continue;
+                        }
+                    },
+                }
+
+                let [r, g, b, a] = px.map(|v| v as usize);
+                index[(r * 3 + g * 5 + b * 7 + a * 11) % 64] = px;
+                pixels.push(px);
+            }
+
+            Self::from_pixels(width, height, pixels).ok_or_else(invalid_size)
+        }
+
+        /// Reads a texture from a PNG file.
+        #[cfg(feature = "png")]
+        pub fn from_png(reader: impl Read) -> Result<Self> {
+            let mut decoder = png::Decoder::new(reader);
+            decoder.set_transformations(png::Transformations::normalize_to_color8());
+            let mut reader = decoder.read_info().map_err(png_error)?;
+
+            let mut buf = alloc::vec![0; reader.output_buffer_size()];
+            let info = reader.next_frame(&mut buf).map_err(png_error)?;
+            let buf = &buf[..info.buffer_size()];
+            let (width, height) = (info.width as usize, info.height as usize);
+
+            let pixels = match info.color_type {
+                png::ColorType::Rgba => {
+                    return Self::from_rgba8(width, height, buf).ok_or_else(invalid_size)
+                }
+                png::ColorType::Rgb => {
+                    return Self::from_rgb8(width, height, buf).ok_or_else(invalid_size)
+                }
+                png::ColorType::Grayscale => buf.iter().map(|&v| [v, v, v, 255]).collect(),
+                png::ColorType::GrayscaleAlpha => buf
+                    .chunks_exact(2)
+                    .map(|v| [v[0], v[0], v[0], v[1]])
+                    .collect(),
+                png::ColorType::Indexed => return Err(invalid("unexpanded PNG palette")),
+            };
+
+            Self::from_pixels(width, height, pixels).ok_or_else(invalid_size)
+        }
+
+        /// Reads a texture, choosing the format based on file magic.
+        ///
+        /// PPM and QOI files are always supported, while PNG files require the `png` feature.
+        pub fn read(mut reader: impl BufRead) -> Result<Self> {
+            let magic = reader.fill_buf()?;
+
+            if magic.starts_with(b"P6") || magic.starts_with(b"P3") {
+                Self::from_ppm(reader)
+            } else if magic.starts_with(b"qoif") {
+                Self::from_qoi(reader)
+            } else if magic.starts_with(b"\x89PNG") {
+                #[cfg(feature = "png")]
+                return Self::from_png(reader);
+                #[cfg(not(feature = "png"))]
+                Err(Error::new(
+                    ErrorKind::Unsupported,
+                    "PNG support is not enabled",
+                ))
+            } else {
+                Err(invalid("unknown image format"))
+            }
+        }
+
+        /// Opens an image file.
+        ///
+        /// See [`Texture::read`] for supported formats.
+        pub fn open(path: impl AsRef<std::path::Path>) -> Result<Self> {
+            Self::read(std::io::BufReader::new(std::fs::File::open(path)?))
+        }
+    }
+
+    #[cfg(feature = "png")]
+    fn png_error(err: png::DecodingError) -> Error {
+        match err {
+            png::DecodingError::IoError(err) => err,
+            err => invalid(alloc::format!("{err}")),
+        }
+    }
+
+    /// Reads a whitespace separated number from a PPM file, skipping comments.
+    fn ppm_number(reader: &mut impl BufRead) -> Result<usize> {
+        let mut num = None::<usize>;
+        let mut byte = [0];
+
+        loop {
+            if reader.read(&mut byte)? == 0 {
+                break;
+            }
+
+            match byte[0] {
+                b'#' if num.is_none() => {
+                    reader.read_line(&mut alloc::string::String::new())?;
+                }
+                c if c.is_ascii_whitespace() => {
+                    if num.is_some() {
+                        break;
+                    }
+                }
+                c if c.is_ascii_digit() => {
+                    num = num
+                        .unwrap_or(0)
+                        .checked_mul(10)
+                        .and_then(|v| v.checked_add((c - b'0') as usize));
+                    if num.is_none() {
+                        return Err(invalid("PPM number overflow"));
+                    }
+                }
+                _ => return Err(invalid("invalid PPM number")),
+            }
+        }
+
+        num.ok_or_else(|| Error::new(ErrorKind::UnexpectedEof, "PPM data truncated"))
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use std::io::ErrorKind;
+
+    fn texels(texture: &Texture) -> Vec<[u8; 4]> {
+        (0..texture.height())
+            .flat_map(|y| (0..texture.width()).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                texture
+                    .pixel(x, y)
+                    .map(|v| (v * 255.0).round() as u8)
+                    .into()
+            })
+            .collect()
+    }
+
+    fn qoi(width: u32, height: u32, data: &[u8]) -> Vec<u8> {
+        let mut file = b"qoif".to_vec();
+        file.extend(width.to_be_bytes());
+        file.extend(height.to_be_bytes());
+        file.extend([4, 0]);
+        file.extend(data);
+        file
+    }
+
+    #[test]
+    fn ppm_ascii() {
+        let data = "P3\n# comment\n2 1 # size\n15\n15 0 0  0 15 5\n";
+        let texture = Texture::read(data.as_bytes()).unwrap();
+
+        assert_eq!((texture.width(), texture.height()), (2, 1));
+        assert_eq!(texels(&texture), [[255, 0, 0, 255], [0, 255, 85, 255]]);
+    }
+
+    #[test]
+    fn ppm_binary() {
+        let texture = Texture::from_ppm(&b"P6 1 2 255\n\x01\x02\x03\x04\x05\x06"[..]).unwrap();
+        assert_eq!(texels(&texture), [[1, 2, 3, 255], [4, 5, 6, 255]]);
+
+        // Values over 255 take 2 big endian bytes.
+        let texture = Texture::from_ppm(&b"P6 1 1 65535\n\xff\xff\x00\x00\x80\x00"[..]).unwrap();
+        assert_eq!(texels(&texture), [[255, 0, 128, 255]]);
+    }
+
+    #[test]
+    fn ppm_truncated() {
+        for data in [
+            &b"P6 2 1 255\n\x01\x02\x03"[..],
+            b"P3 1 1 255 1 2",
+            b"P6 2",
+            b"P",
+        ] {
+            let err = Texture::from_ppm(data).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::UnexpectedEof, "{data:?}");
+        }
+    }
+
+    #[test]
+    fn ppm_invalid() {
+        for data in [
+            &b"P5 1 1 255\n\x00"[..],
+            b"P6 16385 1 255\n",
+            b"P6 99999999999999999999999 1 255\n",
+            b"P6 1 1 0\n\x00\x00\x00",
+            b"P6 1 1 65536\n",
+            b"P6 0 0 255\n",
+            b"P3 1 1 255 1 -2 3",
+        ] {
+            let err = Texture::from_ppm(data).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::InvalidData, "{data:?}");
+        }
+    }
+
+    #[test]
+    fn qoi_ops() {
+        let data = [
+            0xfe, 255, 0, 0,    // RGB
+            0xc0, // Run of 1
+            0xff, 0, 0, 255, 128,  // RGBA
+            0x32, // Index of the red pixel
+            0x7f, // Diff of +1 to every channel
+            0xa1, 0x88, // Luma of +1 to every channel
+        ];
+        let texture = Texture::read(&qoi(3, 2, &data)[..]).unwrap();
+
+        assert_eq!(
+            texels(&texture),
+            [
+                [255, 0, 0, 255],
+                [255, 0, 0, 255],
+                [0, 0, 255, 128],
+                [255, 0, 0, 255],
+                [0, 1, 1, 255],
+                [1, 2, 2, 255],
+            ]
+        );
+    }
+
+    #[test]
+    fn qoi_long_run() {
+        // Runs past the end of the image are cut off.
+        let texture = Texture::from_qoi(&qoi(2, 2, &[0xfd, 0xfd])[..]).unwrap();
+        assert_eq!(texels(&texture), [[0, 0, 0, 255]; 4]);
+    }
+
+    #[test]
+    fn qoi_truncated() {
+        let err = Texture::from_qoi(&qoi(2, 1, &[0xfe, 1, 2, 3])[..]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+
+        let err = Texture::from_qoi(&qoi(1, 1, &[0xfe, 1])[..]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+
+        let err = Texture::from_qoi(&b"qoif\0\0"[..]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn qoi_invalid() {
+        for file in [
+            qoi(16385, 1, &[]),
+            qoi(u32::MAX, u32::MAX, &[]),
+            qoi(0, 1, &[]),
+        ] {
+            let err = Texture::from_qoi(&file[..]).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::InvalidData);
+        }
+
+        let mut file = qoi(1, 1, &[0xfe, 1, 2, 3]);
+        file[0] = b'x';
+        let err = Texture::read(&file[..]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn raw_sizes() {
+        assert!(Texture::from_rgb8(2, 1, &[0; 6]).is_some());
+        assert!(Texture::from_rgb8(2, 1, &[0; 5]).is_none());
+        assert!(Texture::from_rgba8(0, 0, &[]).is_none());
+        assert!(Texture::from_rgba8(usize::MAX, 2, &[]).is_none());
+    }
+}
@@ -0,0 +1,426 @@
+//! Voxel grids.
+
+use crate::{mesh::Mesh, Vector2, Vector3};
+use alloc::{borrow::Cow, collections::BTreeMap, vec, vec::Vec};
+
+/// Largest absolute coordinate of sparse grid cells.
+///
+/// Every integer up to this value is exactly representable as `f32`, which keeps mesh vertices
+/// precise, and cell bounds far from overflowing.
+pub const MAX_SPARSE_COORD: i32 = 1 << 24;
+
+/// How many times the bounding box of a grid may exceed its cell count for greedy meshing.
+const GREEDY_MAX_SPARSITY: u64 = 64;
+
+/// Storage of voxel cells.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum VoxelStorage {
+    /// Fixed size grid, spanning from the origin to `size`.
+    ///
+    /// Cells are stored in X-major order, with empty cells set to `None`.
+    Dense {
+        size: [usize; 3],
+        cells: Vec<Option<Vector3>>,
+    },
+    /// Grid, that only stores filled cells, within [`MAX_SPARSE_COORD`] of the origin.
+    Sparse(
+        #[cfg_attr(feature = "serde", serde(with = "sparse_serde"))] BTreeMap<[i32; 3], Vector3>,
+    ),
+}
+
+/// Grid of colored cubic cells.
+///
+/// Cell at position `[x, y, z]` occupies the unit cube between `[x, y, z]` and
+/// `[x + 1, y + 1, z + 1]` in model space. Before rendering, the grid is turned into a triangle
+/// [`Mesh`] that only contains faces between filled and empty cells. Optionally, coplanar faces of
+/// the same color are merged into larger quads (greedy meshing), which greatly reduces the number
+/// of triangles in uniformly colored regions. Greedy meshing scans the whole bounding box of the
+/// grid, thus scattered sparse cells are meshed face by face regardless.
+///
+/// The mesh is cached by [`VoxelGrid::update_mesh`], until the grid is modified. Grids without an
+/// up to date mesh are meshed again every time they are rendered.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "VoxelGridDesc"))]
+pub struct VoxelGrid {
+    storage: VoxelStorage,
+    #[cfg_attr(feature = "serde", serde(default))]
+    greedy: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    mesh: Option<Mesh>,
+}
+
+/// Serialized form of [`VoxelGrid`], which is validated on deserialization.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct VoxelGridDesc {
+    storage: VoxelStorage,
+    #[serde(default)]
+    greedy: bool,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<VoxelGridDesc> for VoxelGrid {
+    type Error = &'static str;
+
+    fn try_from(desc: VoxelGridDesc) -> Result<Self, Self::Error> {
+        let mut grid = Self::from_storage(desc.storage).ok_or("invalid voxel storage")?;
+        grid.greedy = desc.greedy;
+        grid.update_mesh();
+        Ok(grid)
+    }
+}
+
+impl VoxelGrid {
+    /// Creates an empty dense grid of given size.
+    ///
+    /// # Panics
+    ///
+    /// If the grid does not fit in memory, or its size does not fit in `i32`.
+    pub fn dense(size: [usize; 3]) -> Self {
+        let len = Self::dense_len(size).expect("voxel grid too large");
+        Self::from_storage(VoxelStorage::Dense {
+            size,
+            cells: vec![None; len],
+        })
+        .expect("voxel grid too large")
+    }
+
+    /// Creates an empty sparse grid.
+    pub fn sparse() -> Self {
+        Self {
+            storage: VoxelStorage::Sparse(BTreeMap::new()),
+            greedy: false,
+            mesh: None,
+        }
+    }
+
+    /// Creates a grid from existing cells.
+    ///
+    /// Returns `None`, if dense cells do not match the size of the grid, or if sparse cells are
+    /// further than [`MAX_SPARSE_COORD`] from the origin.
+    pub fn from_storage(storage: VoxelStorage) -> Option<Self> {
+        let valid = match &storage {
+            VoxelStorage::Dense { size, cells } => Self::dense_len(*size) == Some(cells.len()),
+            VoxelStorage::Sparse(cells) => cells.keys().all(Self::sparse_in_range),
+        };
+
+        valid.then_some(Self {
+            storage,
+            greedy: false,
+            mesh: None,
+        })
+    }
+
+    /// Returns the number of cells of a dense grid, if its size is valid.
+    fn dense_len(size: [usize; 3]) -> Option<usize> {
+        if size.iter().any(|&v| i32::try_from(v).is_err()) {
+            return None;
+        }
+        size[0].checked_mul(size[1])?.checked_mul(size[2])
+    }
+
+    fn sparse_in_range(pos: &[i32; 3]) -> bool {
+        pos.iter()
+            .all(|v| (-MAX_SPARSE_COORD..=MAX_SPARSE_COORD).contains(v))
+    }
+
+    pub fn storage(&self) -> &VoxelStorage {
+        &self.storage
+    }
+
+    /// Returns whether greedy meshing is enabled.
+    pub fn greedy(&self) -> bool {
+        self.greedy
+    }
+
+    /// Enables or disables greedy meshing.
+    pub fn set_greedy(&mut self, greedy: bool) {
+        if self.greedy != greedy {
+            self.greedy = greedy;
+            self.mesh = None;
+        }
+    }
+
+    fn dense_idx(size: [usize; 3], [x, y, z]: [i32; 3]) -> Option<usize> {
+        let [x, y, z] = [x, y, z].map(|v| usize::try_from(v).ok());
+        match (x?, y?, z?) {
+            (x, y, z) if x < size[0] && y < size[1] && z < size[2] => {
+                Some(x + size[0] * (y + size[1] * z))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the color of the cell at given position, if it is filled.
+    pub fn get(&self, pos: [i32; 3]) -> Option<Vector3> {
+        match &self.storage {
+            VoxelStorage::Dense { size, cells } => cells[Self::dense_idx(*size, pos)?],
+            VoxelStorage::Sparse(cells) => cells.get(&pos).copied(),
+        }
+    }
+
+    /// Fills or clears the cell at given position.
+    ///
+    /// # Panics
+    ///
+    /// If the position is out of bounds of a dense grid, or further than [`MAX_SPARSE_COORD`] from
+    /// the origin in a sparse one.
+    pub fn set(&mut self, pos: [i32; 3], color: Option<Vector3>) {
+        match &mut self.storage {
+            VoxelStorage::Dense { size, cells } => {
+                let idx = Self::dense_idx(*size, pos).expect("voxel out of bounds");
+                cells[idx] = color;
+            }
+            VoxelStorage::Sparse(cells) => {
+                if let Some(color) = color {
+                    assert!(Self::sparse_in_range(&pos), "voxel out of bounds");
+                    cells.insert(pos, color);
+                } else {
+                    cells.remove(&pos);
+                }
+            }
+        }
+        self.mesh = None;
+    }
+
+    /// Returns the bounds of the grid (inclusive minimum, exclusive maximum).
+    ///
+    /// Returns `None` for empty sparse grids.
+    pub fn bounds(&self) -> Option<([i32; 3], [i32; 3])> {
+        match &self.storage {
+            VoxelStorage::Dense { size, .. } => Some(([0; 3], size.map(|v| v as i32))),
+            VoxelStorage::Sparse(cells) => {
+                let mut iter = cells.keys();
+                let first = *iter.next()?;
+                Some(iter.fold((first, first.map(|v| v + 1)), |(min, max), pos| {
+                    (
+                        [0, 1, 2].map(|i| min[i].min(pos[i])),
+                        [0, 1, 2].map(|i| max[i].max(pos[i] + 1)),
+                    )
+                }))
+            }
+        }
+    }
+
+    /// Returns the mesh of the grid.
+    ///
+    /// If the cached mesh is not up to date, a new one is built, without caching it.
+    pub fn mesh(&self) -> Cow<'_, Mesh> {
+        match &self.mesh {
+            Some(mesh) => Cow::Borrowed(mesh),
+            None => Cow::Owned(self.build_mesh()),
+        }
+    }
+
+    /// Builds and caches the mesh of the grid, if the cached one is not up to date.
+    pub fn update_mesh(&mut self) {
+        if self.mesh.is_none() {
+            self.mesh = Some(self.build_mesh());
+        }
+    }
+
+    fn build_mesh(&self) -> Mesh {
+        let mut builder = MeshBuilder::default();
+        if self.greedy && self.greedy_feasible() {
+            self.mesh_greedy(&mut builder);
+        } else {
+            self.mesh_culled(&mut builder);
+        }
+        builder.mesh
+    }
+
+    /// Returns whether the bounding box is small enough to be scanned by greedy meshing.
+    fn greedy_feasible(&self) -> bool {
+        let Some((min, max)) = self.bounds() else {
+            return true;
+        };
+
+        let cells = match &self.storage {
+            VoxelStorage::Dense { cells, .. } => cells.len(),
+            VoxelStorage::Sparse(cells) => cells.len(),
+        };
+
+        (0..3)
+            .try_fold(1u64, |acc, i| acc.checked_mul((max[i] - min[i]) as u64))
+            .is_some_and(|volume| volume <= (cells as u64).saturating_mul(GREEDY_MAX_SPARSITY))
+    }
+
+    /// Emits one quad for every visible cell face.
+    fn mesh_culled(&self, builder: &mut MeshBuilder) {
+        let mut visit = |pos: [i32; 3], color: Vector3| {
+            for axis in 0..3 {
+                for positive in [false, true] {
+                    let mut neighbor = pos;
+                    neighbor[axis] += if positive { 1 } else { -1 };
+                    if self.get(neighbor).is_none() {
+                        builder.quad(axis, positive, pos, [1, 1], color);
+                    }
+                }
+            }
+        };
+
+        match &self.storage {
+            VoxelStorage::Dense { size, cells } => {
+                for (i, cell) in cells.iter().enumerate() {
+                    if let Some(color) = cell {
+                        let pos = [
+                            i % size[0],
+                            (i / size[0]) % size[1],
+                            i / (size[0] * size[1]),
+                        ];
+                        visit(pos.map(|v| v as i32), *color);
+                    }
+                }
+            }
+            VoxelStorage::Sparse(cells) => {
+                for (pos, color) in cells {
+                    visit(*pos, *color);
+                }
+            }
+        }
+    }
+
+    /// Merges visible faces of the same color into rectangles, slice by slice.
+    fn mesh_greedy(&self, builder: &mut MeshBuilder) {
+        let Some((min, max)) = self.bounds() else {
+            return;
+        };
+
+        for axis in 0..3 {
+            let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+            let (width, height) = ((max[u] - min[u]) as usize, (max[v] - min[v]) as usize);
+            let mut mask = vec![None; width * height];
+
+            for positive in [false, true] {
+                for d in min[axis]..max[axis] {
+                    // Build the mask of visible faces in this slice
+                    for (i, m) in mask.iter_mut().enumerate() {
+                        let mut pos = [0; 3];
+                        pos[axis] = d;
+                        pos[u] = min[u] + (i % width) as i32;
+                        pos[v] = min[v] + (i / width) as i32;
+                        let mut neighbor = pos;
+                        neighbor[axis] += if positive { 1 } else { -1 };
+                        *m = self.get(pos).filter(|_| self.get(neighbor).is_none());
+                    }
+
+                    // Then cover it with rectangles
+                    for y in 0..height {
+                        let mut x = 0;
+                        while x < width {
+                            let Some(color) = mask[y * width + x] else {
+                                x += 1;
+                                continue;
+                            };
+
+                            let same = |i: usize| mask[i] == Some(color);
+
+                            let w = (x..width).take_while(|&x| same(y * width + x)).count();
+                            let h = (y..height)
+                                .take_while(|&y| (x..x + w).all(|x| same(y * width + x)))
+                                .count();
+
+                            for y in y..y + h {
+                                for m in &mut mask[y * width + x..y * width + x + w] {
+                                    *m = None;
+                                }
+                            }
+
+                            let mut pos = [0; 3];
+                            pos[axis] = d;
+                            pos[u] = min[u] + x as i32;
+                            pos[v] = min[v] + y as i32;
+                            builder.quad(axis, positive, pos, [w as i32, h as i32], color);
+
+                            x += w;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct MeshBuilder {
+    mesh: Mesh,
+}
+
+impl MeshBuilder {
+    /// Pushes a face of the cell at `pos`, extended by `extent` cells along the face plane.
+    fn quad(
+        &mut self,
+        axis: usize,
+        positive: bool,
+        pos: [i32; 3],
+        extent: [i32; 2],
+        color: Vector3,
+    ) {
+        let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+
+        let mut origin = pos;
+        if positive {
+            origin[axis] += 1;
+        }
+
+        let corner = |du: i32, dv: i32| {
+            let mut p = origin;
+            p[u] += du;
+            p[v] += dv;
+            Vector3::new(p[0] as f32, p[1] as f32, p[2] as f32)
+        };
+
+        let mesh = &mut self.mesh;
+        let base = mesh.vertices.len();
+
+        // The U and V axis cross into the positive direction of the face axis.
+        mesh.vertices.extend([
+            corner(0, 0),
+            corner(extent[0], 0),
+            corner(extent[0], extent[1]),
+            corner(0, extent[1]),
+        ]);
+        mesh.colors.get_or_insert_with(Vec::new).extend([color; 4]);
+        // Tile textures once per cell.
+        let (w, h) = (extent[0] as f32, extent[1] as f32);
+        mesh.uvs.get_or_insert_with(Vec::new).extend([
+            Vector2::new(0.0, 0.0),
+            Vector2::new(w, 0.0),
+            Vector2::new(w, h),
+            Vector2::new(0.0, h),
+        ]);
+
+        if positive {
+            mesh.indices.push([base, base + 1, base + 2]);
+            mesh.indices.push([base, base + 2, base + 3]);
+        } else {
+            mesh.indices.push([base, base + 2, base + 1]);
+            mesh.indices.push([base, base + 3, base + 2]);
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+mod sparse_serde {
+    use super::*;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    // Array keys are not supported by all formats, thus store the cells as a list of pairs.
+
+    pub fn serialize<S: Serializer>(
+        cells: &BTreeMap<[i32; 3], Vector3>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(cells.iter())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<BTreeMap<[i32; 3], Vector3>, D::Error> {
+        Ok(Vec::<([i32; 3], Vector3)>::deserialize(deserializer)?
+            .into_iter()
+            .collect())
+    }
+}
@@ -215,8 +215,8 @@ export default async function ascii_render(id, scene_json, fw, fh, is_ortho, fov
     for (let y = 0; y < h; y++) {
       // Can't really do color, because we'd end up with far too many dom elements.
       // Instead, just render grayscale.
-      const bytes = render_res.slice(y * w, y * w + w).map(v => v.c);
-      const line = String.fromCharCode(...bytes);
+      const chars = render_res.slice(y * w, y * w + w).map(v => v.c);
+      const line = String.fromCodePoint(...chars);
       lines += line;
       lines += "<br/>";
     }